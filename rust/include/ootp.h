@@ -0,0 +1,69 @@
+/*
+ * C interface of the ootp crate, built with the `ffi` feature.
+ *
+ * Build a shared or a static library to link against with one of:
+ *
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ *   cargo rustc --release --lib --features ffi --crate-type staticlib
+ *
+ * producing libootp.so, libootp.dylib or ootp.dll, respectively libootp.a or ootp.lib,
+ * in target/release.
+ */
+
+#ifndef OOTP_H
+#define OOTP_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The call succeeded. */
+#define OOTP_OK 0
+/* A required pointer is null. */
+#define OOTP_ERR_NULL_POINTER -1
+/* The output buffer cannot hold the code and its NUL terminator. */
+#define OOTP_ERR_BUFFER_TOO_SMALL -2
+/* An argument is out of range: `digits` outside 1..=10, a zero `period`,
+ * an unknown algorithm identifier, a code that is not UTF-8 or a window wider than
+ * OOTP_MAX_WINDOW_STEPS. */
+#define OOTP_ERR_INVALID_ARGUMENT -3
+/* The call panicked. The panic did not cross the FFI boundary. */
+#define OOTP_ERR_PANIC -4
+
+/* The widest window ootp_totp_check accepts, in steps either side of the current one. */
+#define OOTP_MAX_WINDOW_STEPS 10
+
+/* Algorithm identifiers, the discriminants of `OtpAlgorithmId`. */
+#define OOTP_ALGORITHM_SHA1 0
+#define OOTP_ALGORITHM_SHA256 1
+#define OOTP_ALGORITHM_SHA512 2
+#define OOTP_ALGORITHM_SHA3_256 3
+#define OOTP_ALGORITHM_SHA3_512 4
+
+/*
+ * Writes the current TOTP code, NUL terminated, into `out_buf`.
+ * Returns OOTP_OK, or one of the OOTP_ERR_* codes, in which case `out_buf`
+ * is left untouched.
+ */
+int32_t ootp_totp_make(const uint8_t *secret_ptr, size_t secret_len, uint32_t digits,
+                       uint64_t period, uint8_t algo_id, uint8_t *out_buf, size_t out_len);
+
+/*
+ * Checks a TOTP code of `otp_len` UTF-8 bytes, without NUL terminator, within
+ * `breadth_secs` seconds of the current time, rounded down to whole steps of `period`.
+ * Returns 1 if the code is valid, 0 if it is not, or one of the OOTP_ERR_* codes, in
+ * particular OOTP_ERR_INVALID_ARGUMENT if `breadth_secs` spans more than
+ * OOTP_MAX_WINDOW_STEPS steps.
+ */
+int32_t ootp_totp_check(const uint8_t *secret_ptr, size_t secret_len, uint32_t digits,
+                        uint64_t period, uint8_t algo_id, const uint8_t *otp_ptr,
+                        size_t otp_len, uint64_t breadth_secs);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OOTP_H */
@@ -0,0 +1,554 @@
+use crate::algorithm::{algorithm_label, parse_algorithm};
+use crate::encoding::{decode_base32, DecodeError};
+use crate::totp::{CreateOption, OtpConfig, SkewPolicy, Totp};
+use std::fmt;
+
+/// The kind of one-time password an account uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum OtpType {
+    Totp,
+    Hotp,
+}
+
+/// A complete description of an account, for backup and export tooling.
+///
+/// The secret is stored Base32 encoded, like in provisioning URIs. With the `serde` feature,
+/// the descriptor can be (de)serialized, e.g. into an encrypted JSON vault.
+/// Its `Debug` output redacts the secret.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDescriptor {
+    pub issuer: String,
+    pub account: String,
+    pub secret_base32: String,
+    pub digits: u32,
+    pub period: u64,
+    pub algorithm: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub otp_type: OtpType,
+    /// The per-account salt, see `Hotp::with_salt`, Base32 encoded. Missing in older
+    /// descriptors, hence empty.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub salt_base32: String,
+}
+
+impl fmt::Debug for AccountDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountDescriptor")
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .field("secret_base32", &"REDACTED")
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("algorithm", &self.algorithm)
+            .field("otp_type", &self.otp_type)
+            .field("salt_base32", &self.salt_base32)
+            .finish()
+    }
+}
+
+/// The complete verification state of a `Totp`, including its window policy, so that a
+/// restored verifier enforces the same tolerance.
+///
+/// The secret is stored Base32 encoded. With the `serde` feature, the state can be
+/// (de)serialized. Its `Debug` output redacts the secret.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TotpState {
+    pub secret_base32: String,
+    pub digits: u32,
+    pub period: u64,
+    pub algorithm: String,
+    pub radix: u32,
+    pub fixed_drift: i64,
+    pub allow_stripped_leading_zeros: bool,
+    pub window_policy: Option<SkewPolicy>,
+    /// The per-account salt, see `Totp::with_salt`. Missing in older states, hence empty.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub salt: Vec<u8>,
+}
+
+impl fmt::Debug for TotpState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TotpState")
+            .field("secret_base32", &"REDACTED")
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("algorithm", &self.algorithm)
+            .field("radix", &self.radix)
+            .field("fixed_drift", &self.fixed_drift)
+            .field(
+                "allow_stripped_leading_zeros",
+                &self.allow_stripped_leading_zeros,
+            )
+            .field("window_policy", &self.window_policy)
+            .field("salt", &self.salt)
+            .finish()
+    }
+}
+
+/// The errors returned when building a `Totp` from an `AccountDescriptor` or a `TotpState`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorError {
+    /// The secret is not valid Base32.
+    Secret(DecodeError),
+    /// The algorithm label is not supported.
+    UnsupportedAlgorithm(String),
+    /// The descriptor does not describe a TOTP account.
+    UnsupportedType(OtpType),
+}
+
+impl fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DescriptorError::Secret(error) => write!(f, "invalid secret: {}", error),
+            DescriptorError::UnsupportedAlgorithm(label) => {
+                write!(f, "unsupported algorithm {:?}", label)
+            }
+            DescriptorError::UnsupportedType(otp_type) => {
+                write!(f, "unsupported type {:?}", otp_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DescriptorError {}
+
+impl From<DecodeError> for DescriptorError {
+    fn from(error: DecodeError) -> Self {
+        DescriptorError::Secret(error)
+    }
+}
+
+/// An authenticator app with known limitations on the parameters it supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KnownApp {
+    /// Google Authenticator, as of its older releases: SHA1, 6 digits and 30 seconds only.
+    GoogleAuthenticator,
+    /// Microsoft Authenticator: SHA1, 6 digits and 30 seconds only.
+    MicrosoftAuthenticator,
+    /// FreeOTP: SHA1, SHA256 and SHA512, with 6 or 8 digits and any period.
+    FreeOtp,
+}
+
+impl KnownApp {
+    fn supports_algorithm(self, label: &str) -> bool {
+        let supported: &[&str] = match self {
+            KnownApp::GoogleAuthenticator | KnownApp::MicrosoftAuthenticator => &["SHA1"],
+            KnownApp::FreeOtp => &["SHA1", "SHA256", "SHA512"],
+        };
+        supported
+            .iter()
+            .any(|supported| supported.eq_ignore_ascii_case(label))
+    }
+
+    fn supports_digits(self, digits: u32) -> bool {
+        match self {
+            KnownApp::GoogleAuthenticator | KnownApp::MicrosoftAuthenticator => digits == 6,
+            KnownApp::FreeOtp => digits == 6 || digits == 8,
+        }
+    }
+
+    fn supports_period(self, period: u64) -> bool {
+        match self {
+            KnownApp::GoogleAuthenticator | KnownApp::MicrosoftAuthenticator => period == 30,
+            KnownApp::FreeOtp => period > 0,
+        }
+    }
+}
+
+impl fmt::Display for KnownApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KnownApp::GoogleAuthenticator => "Google Authenticator",
+            KnownApp::MicrosoftAuthenticator => "Microsoft Authenticator",
+            KnownApp::FreeOtp => "FreeOTP",
+        })
+    }
+}
+
+/// A parameter of an `AccountDescriptor` that a `KnownApp` may not handle correctly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompatibilityWarning {
+    /// The app does not support the algorithm and may silently use SHA1 instead.
+    UnsupportedAlgorithm { app: KnownApp, algorithm: String },
+    /// The app may display a different number of digits.
+    UnsupportedDigits { app: KnownApp, digits: u32 },
+    /// The app may use another period.
+    UnsupportedPeriod { app: KnownApp, period: u64 },
+}
+
+impl fmt::Display for CompatibilityWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompatibilityWarning::UnsupportedAlgorithm { app, algorithm } => {
+                write!(f, "{} not supported by {}", algorithm, app)
+            }
+            CompatibilityWarning::UnsupportedDigits { app, digits } => {
+                write!(f, "{} digits may display incorrectly in {}", digits, app)
+            }
+            CompatibilityWarning::UnsupportedPeriod { app, period } => {
+                write!(
+                    f,
+                    "a {} seconds period may not be honored by {}",
+                    period, app
+                )
+            }
+        }
+    }
+}
+
+impl AccountDescriptor {
+    /**
+    Returns the warnings about the parameters of this descriptor that `app` may not
+    handle correctly, e.g. to warn users during enrollment. Empty if none.
+
+    # Example
+
+    ```
+    use ootp::account::KnownApp;
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let descriptor = Totp::secret(secret, CreateOption::Digits(8)).to_descriptor("OOTP", "Tester");
+    let warnings = descriptor.compatibility(KnownApp::GoogleAuthenticator);
+    assert_eq!(warnings[0].to_string(), "8 digits may display incorrectly in Google Authenticator");
+    ```
+    */
+    pub fn compatibility(&self, app: KnownApp) -> Vec<CompatibilityWarning> {
+        let mut warnings = Vec::new();
+        if !app.supports_algorithm(&self.algorithm) {
+            warnings.push(CompatibilityWarning::UnsupportedAlgorithm {
+                app,
+                algorithm: self.algorithm.clone(),
+            });
+        }
+        if !app.supports_digits(self.digits) {
+            warnings.push(CompatibilityWarning::UnsupportedDigits {
+                app,
+                digits: self.digits,
+            });
+        }
+        if !app.supports_period(self.period) {
+            warnings.push(CompatibilityWarning::UnsupportedPeriod {
+                app,
+                period: self.period,
+            });
+        }
+        warnings
+    }
+}
+
+impl<'a> Totp<'a> {
+    /**
+    Returns the `AccountDescriptor` of this `Totp` for the given `issuer` and `account`.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let descriptor = totp.to_descriptor("OOTP", "Tester");
+    assert_eq!(descriptor.algorithm, "SHA1");
+    ```
+    */
+    pub fn to_descriptor(&self, issuer: &str, account: &str) -> AccountDescriptor {
+        AccountDescriptor {
+            issuer: issuer.to_string(),
+            account: account.to_string(),
+            secret_base32: self.hotp.secret_base32(),
+            digits: self.digits,
+            period: self.period,
+            algorithm: algorithm_label(self.algorithm).to_string(),
+            otp_type: OtpType::Totp,
+            salt_base32: self.hotp.salt_base32(),
+        }
+    }
+
+    /**
+    TOTP instance constructor from an `AccountDescriptor`.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let descriptor = Totp::secret(secret, CreateOption::Default).to_descriptor("OOTP", "Tester");
+    let totp = Totp::from_descriptor(&descriptor).unwrap();
+    ```
+    */
+    pub fn from_descriptor(
+        descriptor: &AccountDescriptor,
+    ) -> Result<Totp<'static>, DescriptorError> {
+        if descriptor.otp_type != OtpType::Totp {
+            return Err(DescriptorError::UnsupportedType(descriptor.otp_type));
+        }
+        let algorithm = parse_algorithm(&descriptor.algorithm)
+            .ok_or_else(|| DescriptorError::UnsupportedAlgorithm(descriptor.algorithm.clone()))?;
+        let secret = decode_base32(&descriptor.secret_base32)?;
+        let salt = decode_base32(&descriptor.salt_base32)?;
+        Ok(Totp::secret(
+            secret,
+            CreateOption::Full {
+                digits: descriptor.digits,
+                period: descriptor.period,
+                algorithm,
+            },
+        )
+        .with_salt(salt))
+    }
+
+    /// Returns the `TotpState` of this `Totp`.
+    pub fn to_state(&self) -> TotpState {
+        TotpState {
+            secret_base32: self.hotp.secret_base32(),
+            digits: self.digits,
+            period: self.period,
+            algorithm: algorithm_label(self.algorithm).to_string(),
+            radix: self.radix,
+            fixed_drift: self.fixed_drift,
+            allow_stripped_leading_zeros: self.allow_stripped_leading_zeros,
+            window_policy: self.window_policy,
+            salt: self.hotp.salt().to_vec(),
+        }
+    }
+
+    /**
+    TOTP instance constructor from a `TotpState`.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption, SkewPolicy};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let policy = SkewPolicy { back_steps: 1, forward_steps: 0, reject_future: true };
+    let totp = Totp::secret(secret, CreateOption::Default).with_window_policy(policy);
+    let restored = Totp::from_state(&totp.to_state()).unwrap();
+    assert_eq!(restored.window_policy, Some(policy));
+    ```
+    */
+    pub fn from_state(state: &TotpState) -> Result<Totp<'static>, DescriptorError> {
+        let algorithm = parse_algorithm(&state.algorithm)
+            .ok_or_else(|| DescriptorError::UnsupportedAlgorithm(state.algorithm.clone()))?;
+        let secret = decode_base32(&state.secret_base32)?;
+        let config = OtpConfig {
+            digits: state.digits,
+            period: state.period,
+            algorithm,
+            radix: state.radix,
+        };
+        let mut totp = Totp::from_config(secret, config)
+            .with_fixed_drift(state.fixed_drift)
+            .with_stripped_leading_zeros(state.allow_stripped_leading_zeros)
+            .with_salt(&state.salt);
+        totp.window_policy = state.window_policy;
+        Ok(totp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AccountDescriptor, CompatibilityWarning, DescriptorError, KnownApp, OtpType, TotpState,
+    };
+    use crate::encoding::DecodeError;
+    use crate::totp::tests::FixedClock;
+    use crate::totp::{CreateOption, SkewPolicy, Totp};
+
+    fn descriptor() -> AccountDescriptor {
+        let secret = "12345678901234567890123456789012".as_bytes().to_vec();
+        let totp = Totp::secret(
+            secret,
+            CreateOption::Full {
+                digits: 8,
+                period: 60,
+                algorithm: &hmacsha::ShaTypes::Sha2_256,
+            },
+        );
+        totp.to_descriptor("OOTP", "Tester")
+    }
+
+    #[test]
+    fn descriptor_round_trip_test() {
+        let descriptor = descriptor();
+        assert_eq!(descriptor.issuer, "OOTP");
+        assert_eq!(descriptor.account, "Tester");
+        assert_eq!(
+            descriptor.secret_base32,
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA"
+        );
+        assert_eq!(descriptor.digits, 8);
+        assert_eq!(descriptor.period, 60);
+        assert_eq!(descriptor.algorithm, "SHA256");
+        assert_eq!(descriptor.otp_type, OtpType::Totp);
+        let totp = Totp::from_descriptor(&descriptor).unwrap();
+        assert_eq!(totp.to_descriptor("OOTP", "Tester"), descriptor);
+        assert!(!format!("{:?}", descriptor).contains(&descriptor.secret_base32));
+        assert_eq!(descriptor.salt_base32, "");
+    }
+
+    #[test]
+    fn descriptor_salt_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_salt(b"account-1");
+        let descriptor = totp.to_descriptor("OOTP", "Tester");
+        assert_eq!(descriptor.salt_base32, "MFRWG33VNZ2C2MI");
+        let restored = Totp::from_descriptor(&descriptor).unwrap();
+        assert_eq!(restored.hotp.salt(), b"account-1");
+        assert_eq!(restored.make_time(59), totp.make_time(59));
+    }
+
+    #[test]
+    fn descriptor_codes_test() {
+        let totp = Totp::from_descriptor(&descriptor()).unwrap();
+        // RFC 6238 SHA-256 vector at T = 59 with a 30 seconds period, i.e. step 1.
+        assert_eq!(totp.make_time(60), "46119246");
+    }
+
+    #[test]
+    fn descriptor_errors_test() {
+        let mut descriptor = descriptor();
+        descriptor.algorithm = "MD5".to_string();
+        assert_eq!(
+            Totp::from_descriptor(&descriptor).err(),
+            Some(DescriptorError::UnsupportedAlgorithm("MD5".to_string()))
+        );
+        descriptor.algorithm = "SHA1".to_string();
+        descriptor.secret_base32 = "GEZDGNBV1".to_string();
+        assert_eq!(
+            Totp::from_descriptor(&descriptor).err(),
+            Some(DescriptorError::Secret(DecodeError::InvalidCharacter('1')))
+        );
+        descriptor.otp_type = OtpType::Hotp;
+        assert_eq!(
+            Totp::from_descriptor(&descriptor).err(),
+            Some(DescriptorError::UnsupportedType(OtpType::Hotp))
+        );
+    }
+
+    #[test]
+    fn compatibility_test() {
+        let mut descriptor = descriptor();
+        descriptor.algorithm = "SHA512".to_string();
+        let warnings = descriptor.compatibility(KnownApp::GoogleAuthenticator);
+        assert_eq!(
+            warnings,
+            [
+                CompatibilityWarning::UnsupportedAlgorithm {
+                    app: KnownApp::GoogleAuthenticator,
+                    algorithm: "SHA512".to_string(),
+                },
+                CompatibilityWarning::UnsupportedDigits {
+                    app: KnownApp::GoogleAuthenticator,
+                    digits: 8,
+                },
+                CompatibilityWarning::UnsupportedPeriod {
+                    app: KnownApp::GoogleAuthenticator,
+                    period: 60,
+                },
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "SHA512 not supported by Google Authenticator"
+        );
+        assert_eq!(
+            warnings[1].to_string(),
+            "8 digits may display incorrectly in Google Authenticator"
+        );
+        assert!(descriptor.compatibility(KnownApp::FreeOtp).is_empty());
+        descriptor.digits = 7;
+        assert_eq!(
+            descriptor.compatibility(KnownApp::FreeOtp),
+            [CompatibilityWarning::UnsupportedDigits {
+                app: KnownApp::FreeOtp,
+                digits: 7,
+            }]
+        );
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let defaults = Totp::secret(secret, CreateOption::Default).to_descriptor("OOTP", "Tester");
+        assert!(defaults
+            .compatibility(KnownApp::MicrosoftAuthenticator)
+            .is_empty());
+    }
+
+    const POLICY: SkewPolicy = SkewPolicy {
+        back_steps: 2,
+        forward_steps: 1,
+        reject_future: true,
+    };
+
+    fn state() -> TotpState {
+        Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_fixed_drift(1)
+        .with_window_policy(POLICY)
+        .with_salt(b"salt")
+        .to_state()
+    }
+
+    #[test]
+    fn state_round_trip_test() {
+        let state = state();
+        assert_eq!(state.window_policy, Some(POLICY));
+        assert_eq!(state.fixed_drift, 1);
+        assert_eq!(state.salt, b"salt");
+        let totp = Totp::from_state(&state).unwrap();
+        assert_eq!(totp.to_state(), state);
+        assert!(!format!("{:?}", state).contains(&state.secret_base32));
+        let mut invalid = state;
+        invalid.algorithm = "MD5".to_string();
+        assert_eq!(
+            Totp::from_state(&invalid).err(),
+            Some(DescriptorError::UnsupportedAlgorithm("MD5".to_string()))
+        );
+    }
+
+    #[test]
+    fn state_window_policy_test() {
+        let clock = FixedClock(1_111_111_109);
+        let totp = Totp::from_state(&state()).unwrap().with_clock(&clock);
+        assert!(totp.check(&totp.make_drift(-2), None));
+        assert!(!totp.check(&totp.make_drift(-3), None));
+        assert!(!totp.check(&totp.make_drift(1), None));
+        // An explicit breadth overrides the policy.
+        assert!(totp.check(&totp.make_drift(1), Some(1)));
+        let mut state = state();
+        state.window_policy = None;
+        let totp = Totp::from_state(&state).unwrap().with_clock(&clock);
+        assert!(totp.check(&totp.make_drift(1), None));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn state_serde_test() {
+        let json = serde_json::to_string(&state()).unwrap();
+        assert!(json.contains("\"back_steps\":2"));
+        let decoded: TotpState = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, state());
+        let clock = FixedClock(1_111_111_109);
+        let totp = Totp::from_state(&decoded).unwrap().with_clock(&clock);
+        assert!(!totp.check(&totp.make_drift(1), None));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn descriptor_serde_test() {
+        let descriptor = descriptor();
+        let json = serde_json::to_string(&descriptor).unwrap();
+        assert!(json.contains("\"type\":\"totp\""));
+        let decoded: AccountDescriptor = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, descriptor);
+    }
+}
@@ -0,0 +1,216 @@
+use hmacsha::ShaTypes;
+
+/**
+Returns the label of `algorithm`, as used by the `algorithm` parameter of provisioning URIs.
+
+# Example
+
+```
+use ootp::algorithm::algorithm_label;
+use ootp::hmacsha::ShaTypes;
+
+assert_eq!(algorithm_label(&ShaTypes::Sha2_256), "SHA256");
+```
+*/
+pub const fn algorithm_label(algorithm: &ShaTypes) -> &'static str {
+    match algorithm {
+        ShaTypes::Sha1 => "SHA1",
+        ShaTypes::Sha2_256 => "SHA256",
+        ShaTypes::Sha2_512 => "SHA512",
+        ShaTypes::Sha3_256 => "SHA3-256",
+        ShaTypes::Sha3_512 => "SHA3-512",
+    }
+}
+
+/**
+Returns the length, in bytes, of the HMAC digest of `algorithm`.
+
+# Example
+
+```
+use ootp::algorithm::digest_len;
+use ootp::hmacsha::ShaTypes;
+
+assert_eq!(digest_len(&ShaTypes::Sha1), 20);
+assert_eq!(digest_len(&ShaTypes::Sha3_512), 64);
+```
+*/
+pub const fn digest_len(algorithm: &ShaTypes) -> usize {
+    match algorithm {
+        ShaTypes::Sha1 => 20,
+        ShaTypes::Sha2_256 | ShaTypes::Sha3_256 => 32,
+        ShaTypes::Sha2_512 | ShaTypes::Sha3_512 => 64,
+    }
+}
+
+/**
+Parses an algorithm label, case-insensitively. Returns `None` for unsupported algorithms,
+including `SHA224` and `SHA384`, which the `hmacsha` backend does not implement.
+
+# Example
+
+```
+use ootp::algorithm::parse_algorithm;
+use ootp::hmacsha::ShaTypes;
+
+assert!(matches!(parse_algorithm("sha512"), Some(ShaTypes::Sha2_512)));
+assert!(parse_algorithm("MD5").is_none());
+```
+*/
+pub fn parse_algorithm(label: &str) -> Option<&'static ShaTypes> {
+    supported_algorithms()
+        .iter()
+        .find(|(supported, _)| supported.eq_ignore_ascii_case(label))
+        .map(|&(_, algorithm)| algorithm)
+}
+
+/**
+Returns the label and value of every supported algorithm, e.g. to fill a configuration form.
+
+The truncated SHA-2 variants, SHA-224 and SHA-384, are not supported: `hmacsha::ShaTypes`
+has no such variants.
+
+# Example
+
+```
+use ootp::algorithm::supported_algorithms;
+
+let labels: Vec<&str> = supported_algorithms().iter().map(|&(label, _)| label).collect();
+assert_eq!(labels, ["SHA1", "SHA256", "SHA512", "SHA3-256", "SHA3-512"]);
+```
+*/
+pub const fn supported_algorithms() -> &'static [(&'static str, &'static ShaTypes)] {
+    &[
+        ("SHA1", &ShaTypes::Sha1),
+        ("SHA256", &ShaTypes::Sha2_256),
+        ("SHA512", &ShaTypes::Sha2_512),
+        ("SHA3-256", &ShaTypes::Sha3_256),
+        ("SHA3-512", &ShaTypes::Sha3_512),
+    ]
+}
+
+/**
+An algorithm identifier with explicit, stable discriminants, e.g. to pass the algorithm
+as a plain integer across an FFI boundary.
+
+# Example
+
+```
+use ootp::algorithm::{algorithm_label, OtpAlgorithmId};
+
+let id = OtpAlgorithmId::from_u8(1).unwrap();
+assert_eq!(algorithm_label(id.sha_type()), "SHA256");
+```
+*/
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OtpAlgorithmId {
+    Sha1 = 0,
+    Sha256 = 1,
+    Sha512 = 2,
+    Sha3_256 = 3,
+    Sha3_512 = 4,
+}
+
+impl OtpAlgorithmId {
+    /// Returns the identifier of discriminant `id`, or `None` if there is none.
+    pub const fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(OtpAlgorithmId::Sha1),
+            1 => Some(OtpAlgorithmId::Sha256),
+            2 => Some(OtpAlgorithmId::Sha512),
+            3 => Some(OtpAlgorithmId::Sha3_256),
+            4 => Some(OtpAlgorithmId::Sha3_512),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier of `algorithm`.
+    pub const fn from_sha_type(algorithm: &ShaTypes) -> Self {
+        match algorithm {
+            ShaTypes::Sha1 => OtpAlgorithmId::Sha1,
+            ShaTypes::Sha2_256 => OtpAlgorithmId::Sha256,
+            ShaTypes::Sha2_512 => OtpAlgorithmId::Sha512,
+            ShaTypes::Sha3_256 => OtpAlgorithmId::Sha3_256,
+            ShaTypes::Sha3_512 => OtpAlgorithmId::Sha3_512,
+        }
+    }
+
+    /// Returns the algorithm identified.
+    pub const fn sha_type(self) -> &'static ShaTypes {
+        match self {
+            OtpAlgorithmId::Sha1 => &ShaTypes::Sha1,
+            OtpAlgorithmId::Sha256 => &ShaTypes::Sha2_256,
+            OtpAlgorithmId::Sha512 => &ShaTypes::Sha2_512,
+            OtpAlgorithmId::Sha3_256 => &ShaTypes::Sha3_256,
+            OtpAlgorithmId::Sha3_512 => &ShaTypes::Sha3_512,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        algorithm_label, digest_len, parse_algorithm, supported_algorithms, OtpAlgorithmId,
+    };
+    use crate::hotp::{Hotp, MakeOption};
+    use hmacsha::HmacSha;
+
+    #[test]
+    fn supported_algorithms_test() {
+        let algorithms = supported_algorithms();
+        for label in ["SHA1", "SHA256", "SHA512"].iter() {
+            assert!(algorithms.iter().any(|(supported, _)| supported == label));
+        }
+        for &(label, algorithm) in algorithms.iter() {
+            assert_eq!(algorithm_label(algorithm), label);
+            let parsed = parse_algorithm(label).unwrap();
+            assert_eq!(algorithm_label(parsed), label);
+            let parsed = parse_algorithm(&label.to_ascii_lowercase()).unwrap();
+            assert_eq!(algorithm_label(parsed), label);
+        }
+        assert!(parse_algorithm("SHA224").is_none());
+        assert!(parse_algorithm("SHA384").is_none());
+        assert!(parse_algorithm("").is_none());
+    }
+
+    #[test]
+    fn digest_len_test() {
+        let expected = [20, 32, 64, 32, 64];
+        for (&(_, algorithm), &len) in supported_algorithms().iter().zip(expected.iter()) {
+            assert_eq!(digest_len(algorithm), len);
+            let digest = HmacSha::new(b"key", b"message", algorithm).compute_digest();
+            assert_eq!(digest.len(), len);
+        }
+    }
+
+    #[test]
+    fn algorithm_id_test() {
+        let secret20 = "12345678901234567890";
+        let secret32 = "12345678901234567890123456789012";
+        let secret64 = "1234567890123456789012345678901234567890123456789012345678901234";
+        // RFC 6238 vectors at time 59 for SHA1/256/512, HMAC-SHA3 computed independently.
+        let expected = [
+            (0, "SHA1", secret20, "94287082"),
+            (1, "SHA256", secret32, "46119246"),
+            (2, "SHA512", secret64, "90693936"),
+            (3, "SHA3-256", secret32, "03503818"),
+            (4, "SHA3-512", secret64, "01892432"),
+        ];
+        for &(discriminant, label, secret, code) in expected.iter() {
+            let id = OtpAlgorithmId::from_u8(discriminant).unwrap();
+            assert_eq!(id as u8, discriminant);
+            assert_eq!(algorithm_label(id.sha_type()), label);
+            assert_eq!(OtpAlgorithmId::from_sha_type(id.sha_type()), id);
+            let hotp = Hotp::new(secret.as_bytes().to_vec());
+            let otp = hotp.make(MakeOption::Full {
+                counter: 1,
+                digits: 8,
+                algorithm: id.sha_type(),
+            });
+            assert_eq!(otp, code);
+        }
+        assert_eq!(OtpAlgorithmId::from_u8(5), None);
+        assert_eq!(OtpAlgorithmId::from_u8(u8::MAX), None);
+    }
+}
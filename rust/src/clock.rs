@@ -0,0 +1,279 @@
+use std::convert::TryFrom;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Instant, SystemTime};
+
+/// A source of the current Unix time, in seconds.
+///
+/// `Totp` reads the time through this trait, so it can be replaced for devices
+/// without a reliable real-time clock or for deterministic tests.
+/// Implementations must be thread-safe so a `Totp` can be shared between threads.
+pub trait TimeSource: Send + Sync {
+    /// Returns the number of seconds elapsed since the UNIX epoch.
+    fn now_secs(&self) -> u64;
+
+    /// Returns the number of milliseconds elapsed since the UNIX epoch.
+    ///
+    /// Defaults to `now_secs` in milliseconds, for sources without sub-second precision.
+    fn now_millis(&self) -> u128 {
+        u128::from(self.now_secs()) * 1000
+    }
+}
+
+/// The default `TimeSource`, backed by `SystemTime`.
+///
+/// With the `env_clock` feature, the time is read from the `OOTP_FAKE_TIME` environment
+/// variable (Unix seconds) when it is set, to make end-to-end tests deterministic.
+/// Never enable this feature in production builds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now_secs(&self) -> u64 {
+        get_unix_epoch()
+    }
+
+    fn now_millis(&self) -> u128 {
+        #[cfg(feature = "env_clock")]
+        {
+            if let Some(time) = fake_time(|name| std::env::var(name).ok()) {
+                return u128::from(time) * 1000;
+            }
+        }
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis())
+    }
+}
+
+/**
+A `TimeSource` deriving the Unix time from a one-time epoch anchor (e.g. an NTP sample)
+and the monotonic time elapsed since `start_instant`.
+
+# Example
+
+```
+use ootp::clock::{AnchoredMonotonicClock, TimeSource};
+use ootp::totp::{CreateOption, Totp};
+use std::time::Instant;
+
+let clock = AnchoredMonotonicClock {
+    anchor_epoch: 1_111_111_109,
+    start_instant: Instant::now(),
+};
+let secret = "A strong shared secret".as_bytes().to_vec();
+let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+assert!(clock.now_secs() >= 1_111_111_109);
+let otp = totp.make();
+```
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct AnchoredMonotonicClock {
+    /// The Unix time, in seconds, sampled at `start_instant`.
+    pub anchor_epoch: u64,
+    /// The monotonic instant at which `anchor_epoch` was sampled.
+    pub start_instant: Instant,
+}
+
+impl TimeSource for AnchoredMonotonicClock {
+    fn now_secs(&self) -> u64 {
+        self.anchor_epoch
+            .saturating_add(self.start_instant.elapsed().as_secs())
+    }
+
+    fn now_millis(&self) -> u128 {
+        (u128::from(self.anchor_epoch) * 1000)
+            .saturating_add(self.start_instant.elapsed().as_millis())
+    }
+}
+
+/**
+A `TimeSource` set and advanced by hand, with millisecond precision, e.g. to probe the
+exact instant a code rolls over in tests.
+
+# Example
+
+```
+use ootp::clock::TestClock;
+use ootp::totp::{CreateOption, Totp};
+
+let clock = TestClock::new(59);
+let secret = "A strong shared secret".as_bytes().to_vec();
+let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+let before = totp.make();
+clock.advance(1);
+assert_ne!(totp.make(), before);
+```
+*/
+#[derive(Debug, Default)]
+pub struct TestClock {
+    millis: Mutex<u128>,
+}
+
+impl TestClock {
+    /// Creates a clock at `secs` seconds since the UNIX epoch.
+    pub const fn new(secs: u64) -> Self {
+        Self {
+            millis: Mutex::new(secs as u128 * 1000),
+        }
+    }
+
+    /// Creates a clock at `millis` milliseconds since the UNIX epoch.
+    pub const fn from_millis(millis: u64) -> Self {
+        Self {
+            millis: Mutex::new(millis as u128),
+        }
+    }
+
+    /// Sets the time to `secs` seconds since the UNIX epoch.
+    pub fn set(&self, secs: u64) {
+        *self.millis() = u128::from(secs) * 1000;
+    }
+
+    /// Sets the time to `millis` milliseconds since the UNIX epoch.
+    pub fn set_millis(&self, millis: u64) {
+        *self.millis() = u128::from(millis);
+    }
+
+    /// Moves the time `secs` seconds forward.
+    pub fn advance(&self, secs: u64) {
+        let mut millis = self.millis();
+        *millis = millis.saturating_add(u128::from(secs) * 1000);
+    }
+
+    /// Moves the time `millis` milliseconds forward.
+    pub fn advance_millis(&self, millis: u64) {
+        let mut now = self.millis();
+        *now = now.saturating_add(u128::from(millis));
+    }
+
+    fn millis(&self) -> MutexGuard<'_, u128> {
+        // The guarded value is always valid, even if a holder panicked.
+        self.millis.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TimeSource for TestClock {
+    fn now_secs(&self) -> u64 {
+        u64::try_from(*self.millis() / 1000).unwrap_or(u64::MAX)
+    }
+
+    fn now_millis(&self) -> u128 {
+        *self.millis()
+    }
+}
+
+/// Returns the Unix seconds of `OOTP_FAKE_TIME`, as read by `read_var`, if it is set and valid.
+#[cfg(feature = "env_clock")]
+fn fake_time(read_var: impl FnOnce(&str) -> Option<String>) -> Option<u64> {
+    read_var("OOTP_FAKE_TIME").and_then(|time| time.trim().parse().ok())
+}
+
+pub(crate) fn get_unix_epoch() -> u64 {
+    #[cfg(feature = "env_clock")]
+    {
+        if let Some(time) = fake_time(|name| std::env::var(name).ok()) {
+            return time;
+        }
+    }
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "env_clock")]
+    use super::fake_time;
+    use super::{AnchoredMonotonicClock, TestClock, TimeSource};
+    use crate::totp::{CreateOption, Totp};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn anchored_monotonic_clock_test() {
+        let anchor_epoch = 1_111_111_080;
+        let clock = AnchoredMonotonicClock {
+            anchor_epoch,
+            start_instant: Instant::now(),
+        };
+        assert!(clock.now_secs() >= anchor_epoch);
+        assert!(clock.now_secs() < anchor_epoch + 30);
+        assert!(clock.now_millis() >= u128::from(anchor_epoch) * 1000);
+    }
+
+    #[test]
+    fn anchored_monotonic_clock_saturation_test() {
+        let clock = AnchoredMonotonicClock {
+            anchor_epoch: u64::MAX,
+            start_instant: Instant::now().checked_sub(Duration::from_secs(5)).unwrap(),
+        };
+        assert_eq!(clock.now_secs(), u64::MAX);
+        assert!(clock.now_millis() >= u128::from(u64::MAX) * 1000 + 5000);
+    }
+
+    #[test]
+    fn anchored_monotonic_clock_advance_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let anchor_epoch = 1_111_111_080;
+        let now = Instant::now();
+        let start = AnchoredMonotonicClock {
+            anchor_epoch,
+            start_instant: now,
+        };
+        // Pretend the anchor was taken 65 seconds ago, i.e. two steps have elapsed.
+        let advanced = AnchoredMonotonicClock {
+            anchor_epoch,
+            start_instant: now.checked_sub(Duration::from_secs(65)).unwrap(),
+        };
+        let totp = Totp::secret(secret.clone(), CreateOption::Digits(8)).with_clock(&start);
+        let advanced_totp = Totp::secret(secret, CreateOption::Digits(8)).with_clock(&advanced);
+        assert_eq!(start.now_secs() / 30 + 2, advanced.now_secs() / 30);
+        assert_eq!(totp.make(), totp.make_time(anchor_epoch));
+        assert_eq!(advanced_totp.make(), totp.make_time(anchor_epoch + 65));
+    }
+
+    #[test]
+    fn test_clock_rollover_test() {
+        let clock = TestClock::new(55);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Digits(8)).with_clock(&clock);
+        let first = totp.make();
+        assert_eq!(first, "94287082");
+        for time in 56..=65 {
+            clock.advance(1);
+            assert_eq!(clock.now_secs(), time);
+            assert_eq!(totp.make() == first, time < 60, "at {}", time);
+        }
+        clock.set_millis(59_999);
+        assert_eq!(totp.make(), first);
+        assert_eq!(totp.remaining_millis(), 1);
+        clock.advance_millis(1);
+        assert_ne!(totp.make(), first);
+        assert_eq!(totp.remaining_millis(), 30_000);
+        clock.set(u64::MAX);
+        assert_eq!(clock.now_secs(), u64::MAX);
+        assert_eq!(clock.now_millis(), u128::from(u64::MAX) * 1000);
+        clock.advance_millis(999);
+        assert_eq!(clock.now_secs(), u64::MAX);
+        clock.advance(u64::MAX);
+        assert_eq!(clock.now_secs(), u64::MAX);
+        assert_eq!(TestClock::new(u64::MAX).now_secs(), u64::MAX);
+    }
+
+    /// Taken from [RFC 6238](https://datatracker.ietf.org/doc/html/rfc6238#appendix-B)
+    #[cfg(feature = "env_clock")]
+    #[test]
+    fn env_clock_test() {
+        let env = |value: &'static str| {
+            move |name: &str| Some(value.to_string()).filter(|_| name == "OOTP_FAKE_TIME")
+        };
+        let time = fake_time(env("1111111111\n")).unwrap();
+        assert_eq!(time, 1_111_111_111);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let clock = TestClock::new(time);
+        let totp = Totp::secret(secret, CreateOption::Digits(8)).with_clock(&clock);
+        assert_eq!(totp.make(), "14050471");
+        assert_eq!(fake_time(env("not a time")), None);
+        assert_eq!(fake_time(env("-1")), None);
+        assert_eq!(fake_time(|_| None), None);
+    }
+}
@@ -2,6 +2,14 @@ use hmacsha::ShaTypes;
 
 pub const DEFAULT_BREADTH: u64 = 0;
 pub const DEFAULT_COUNTER: u64 = 0;
+pub const DEFAULT_COUNTER_LENGTH: usize = 8;
 pub const DEFAULT_DIGITS: u32 = 6;
+/// The most decimal digits a code can have, the length of the largest 32-bit truncated value.
+/// Codes asked with more digits are this long.
+pub const MAX_DIGITS: u32 = 10;
 pub const DEFAULT_PERIOD: u64 = 30;
+pub const DEFAULT_RADIX: u32 = 10;
+/// The maximum length, in bytes, of a code submitted for verification. Longer inputs are
+/// rejected before any processing, so that oversized submissions cannot waste resources.
+pub const MAX_OTP_INPUT_LEN: usize = 64;
 pub const DEFAULT_ALGORITHM: &ShaTypes = &ShaTypes::Sha1;
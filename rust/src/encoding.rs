@@ -0,0 +1,215 @@
+use std::fmt;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// The errors returned when decoding an encoded secret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input contains a character outside of the encoding alphabet.
+    InvalidCharacter(char),
+    /// The input does not decode to a whole number of bytes.
+    InvalidLength,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidCharacter(c) => write!(f, "invalid character {:?}", c),
+            DecodeError::InvalidLength => write!(f, "invalid length"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/**
+Encodes `bytes` as an unpadded RFC 4648 Base32 string, as shown by authenticator apps.
+
+# Example
+
+```
+use ootp::encoding::encode_base32;
+
+assert_eq!(encode_base32(b"Hello!\xDE\xAD\xBE\xEF"), "JBSWY3DPEHPK3PXP");
+```
+*/
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0_u32, 0_u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(
+                BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize],
+            ));
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(char::from(
+            BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize],
+        ));
+    }
+    encoded
+}
+
+/**
+Decodes an RFC 4648 Base32 string, case-insensitively and with or without `=` padding.
+
+# Example
+
+```
+use ootp::encoding::decode_base32;
+
+assert_eq!(decode_base32("JBSWY3DPEHPK3PXP").unwrap(), b"Hello!\xDE\xAD\xBE\xEF");
+```
+*/
+pub fn decode_base32(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let encoded = encoded.trim_end_matches('=');
+    let mut decoded = Vec::with_capacity(encoded.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0_u32, 0_u32);
+    for c in encoded.chars() {
+        let value = match c.to_ascii_uppercase() {
+            upper @ 'A'..='Z' => upper as u32 - 'A' as u32,
+            digit @ '2'..='7' => digit as u32 - '2' as u32 + 26,
+            _ => return Err(DecodeError::InvalidCharacter(c)),
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    // A whole character left over, or set bits left over, means the input was cut
+    // in the middle of a byte.
+    if bits >= 5 || buffer != 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    Ok(decoded)
+}
+
+/**
+Decodes a hexadecimal string, case-insensitively.
+
+# Example
+
+```
+use ootp::encoding::decode_hex;
+
+assert_eq!(decode_hex("DEADbeef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+```
+*/
+pub fn decode_hex(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let nibbles = encoded
+        .chars()
+        .map(|c| c.to_digit(16).ok_or(DecodeError::InvalidCharacter(c)))
+        .collect::<Result<Vec<u32>, DecodeError>>()?;
+    if nibbles.len() % 2 != 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    Ok(nibbles
+        .chunks(2)
+        .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+        .collect())
+}
+
+/**
+Decodes a secret whose encoding is detected heuristically:
+
+- hexadecimal when it only contains `[0-9a-fA-F]` and has an even length,
+- Base32 otherwise, which fails on characters outside of the Base32 alphabet.
+
+Some strings, like "DEADBEEF", are both valid hexadecimal and valid Base32:
+hexadecimal is preferred. Use `decode_base32` when the encoding is known.
+
+# Example
+
+```
+use ootp::encoding::decode_auto;
+
+assert_eq!(decode_auto("DEADBEEF").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+assert_eq!(decode_auto("MZXW6YTBOI").unwrap(), b"foobar");
+```
+*/
+pub fn decode_auto(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    if !encoded.is_empty()
+        && encoded.len().is_multiple_of(2)
+        && encoded.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        decode_hex(encoded)
+    } else {
+        decode_base32(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_auto, decode_base32, decode_hex, encode_base32, DecodeError};
+
+    /// Taken from [RFC 4648](https://datatracker.ietf.org/doc/html/rfc4648#section-10)
+    #[test]
+    fn base32_rfc_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ];
+        for (decoded, encoded) in vectors.iter() {
+            assert_eq!(encode_base32(decoded.as_bytes()), *encoded);
+            assert_eq!(decode_base32(encoded).unwrap(), decoded.as_bytes());
+        }
+        assert_eq!(decode_base32("MZXW6YQ=").unwrap(), b"foob");
+        assert_eq!(decode_base32("mzxw6ytboi").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn base32_errors() {
+        assert_eq!(
+            decode_base32("MZXW6YT1"),
+            Err(DecodeError::InvalidCharacter('1'))
+        );
+        assert_eq!(decode_base32("MZXW6YTBO"), Err(DecodeError::InvalidLength));
+        // 15 characters leave 3 bits of a partial byte, which must be zero.
+        assert_eq!(
+            decode_base32("JBSWY3DPEHPK3PX"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(decode_base32("JBSWY3DPEHPK3PY").unwrap().len(), 9);
+        assert_eq!(decode_base32("MZ"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode_base32("MY").unwrap(), b"f");
+    }
+
+    #[test]
+    fn hex_test() {
+        assert_eq!(
+            decode_hex("3132333435363738393031323334353637383930").unwrap(),
+            b"12345678901234567890"
+        );
+        assert_eq!(decode_hex("abc"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode_hex("zz"), Err(DecodeError::InvalidCharacter('z')));
+    }
+
+    #[test]
+    fn auto_test() {
+        assert_eq!(decode_auto("DEADBEEF").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(decode_auto("deadbeef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            decode_auto("JBSWY3DPEHPK3PXP").unwrap(),
+            b"Hello!\xDE\xAD\xBE\xEF"
+        );
+        // Odd length hexadecimal digits fall back to Base32.
+        assert_eq!(decode_auto("ABC"), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            decode_auto("not a secret!"),
+            Err(DecodeError::InvalidCharacter(' '))
+        );
+    }
+}
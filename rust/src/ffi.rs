@@ -0,0 +1,298 @@
+#![allow(unsafe_code)]
+
+use crate::algorithm::OtpAlgorithmId;
+use crate::constants::MAX_DIGITS;
+use crate::totp::{CreateOption, Totp};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+/// The call succeeded.
+pub const OOTP_OK: i32 = 0;
+/// A required pointer is null.
+pub const OOTP_ERR_NULL_POINTER: i32 = -1;
+/// The output buffer cannot hold the code and its NUL terminator.
+pub const OOTP_ERR_BUFFER_TOO_SMALL: i32 = -2;
+/// An argument is out of range: `digits` outside `1..=10`, a zero `period`,
+/// an unknown algorithm identifier, a code that is not UTF-8 or a window wider than
+/// `OOTP_MAX_WINDOW_STEPS`.
+pub const OOTP_ERR_INVALID_ARGUMENT: i32 = -3;
+/// The call panicked. The panic did not cross the FFI boundary.
+pub const OOTP_ERR_PANIC: i32 = -4;
+/// The widest window `ootp_totp_check` accepts, in steps either side of the current one.
+pub const OOTP_MAX_WINDOW_STEPS: u64 = 10;
+
+/// Builds the `Totp` of the FFI arguments, validating them first.
+fn ffi_totp(secret: &[u8], digits: u32, period: u64, algo_id: u8) -> Result<Totp<'static>, i32> {
+    let algorithm = OtpAlgorithmId::from_u8(algo_id).ok_or(OOTP_ERR_INVALID_ARGUMENT)?;
+    if !(1..=MAX_DIGITS).contains(&digits) || period == 0 {
+        return Err(OOTP_ERR_INVALID_ARGUMENT);
+    }
+    Ok(Totp::secret(
+        secret.to_vec(),
+        CreateOption::Full {
+            digits,
+            period,
+            algorithm: algorithm.sha_type(),
+        },
+    ))
+}
+
+/// Runs `f`, turning a panic into `OOTP_ERR_PANIC`.
+fn guard<F: FnOnce() -> i32>(f: F) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(OOTP_ERR_PANIC)
+}
+
+/**
+Writes the current TOTP code, NUL terminated, into `out_buf`.
+
+`algo_id` is an `OtpAlgorithmId` discriminant. Returns `OOTP_OK`, or one of the
+`OOTP_ERR_*` codes, in which case `out_buf` is left untouched.
+
+# Safety
+
+`secret_ptr` must be valid for reads of `secret_len` bytes and `out_buf` valid for
+writes of `out_len` bytes.
+
+# Example
+
+```
+use ootp::ffi::{ootp_totp_make, OOTP_OK};
+
+let secret = b"A strong shared secret";
+let mut out = [0u8; 7];
+let status = unsafe {
+    ootp_totp_make(secret.as_ptr(), secret.len(), 6, 30, 0, out.as_mut_ptr(), out.len())
+};
+assert_eq!(status, OOTP_OK);
+assert_eq!(out[6], 0);
+```
+*/
+#[no_mangle]
+pub unsafe extern "C" fn ootp_totp_make(
+    secret_ptr: *const u8,
+    secret_len: usize,
+    digits: u32,
+    period: u64,
+    algo_id: u8,
+    out_buf: *mut u8,
+    out_len: usize,
+) -> i32 {
+    if secret_ptr.is_null() || out_buf.is_null() {
+        return OOTP_ERR_NULL_POINTER;
+    }
+    let secret = slice::from_raw_parts(secret_ptr, secret_len);
+    let out = slice::from_raw_parts_mut(out_buf, out_len);
+    guard(|| {
+        let totp = match ffi_totp(secret, digits, period, algo_id) {
+            Ok(totp) => totp,
+            Err(status) => return status,
+        };
+        let otp = totp.make();
+        if out.len() <= otp.len() {
+            return OOTP_ERR_BUFFER_TOO_SMALL;
+        }
+        out[..otp.len()].copy_from_slice(otp.as_bytes());
+        out[otp.len()] = 0;
+        OOTP_OK
+    })
+}
+
+/**
+Checks a TOTP code within `breadth_secs` seconds of the current time, rounded down to
+whole steps of `period`, like `Totp::check_duration`.
+
+`otp_ptr` points to `otp_len` bytes of UTF-8, without NUL terminator. Returns `1` if
+the code is valid, `0` if it is not, or one of the `OOTP_ERR_*` codes, in particular
+`OOTP_ERR_INVALID_ARGUMENT` if `breadth_secs` spans more than `OOTP_MAX_WINDOW_STEPS` steps.
+
+# Safety
+
+`secret_ptr` must be valid for reads of `secret_len` bytes and `otp_ptr` valid for
+reads of `otp_len` bytes.
+
+# Example
+
+```
+use ootp::ffi::{ootp_totp_check, ootp_totp_make};
+
+let secret = b"A strong shared secret";
+let mut otp = [0u8; 7];
+let status = unsafe {
+    ootp_totp_make(secret.as_ptr(), secret.len(), 6, 30, 0, otp.as_mut_ptr(), otp.len());
+    ootp_totp_check(secret.as_ptr(), secret.len(), 6, 30, 0, otp.as_ptr(), 6, 30)
+};
+assert_eq!(status, 1);
+```
+*/
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ootp_totp_check(
+    secret_ptr: *const u8,
+    secret_len: usize,
+    digits: u32,
+    period: u64,
+    algo_id: u8,
+    otp_ptr: *const u8,
+    otp_len: usize,
+    breadth_secs: u64,
+) -> i32 {
+    if secret_ptr.is_null() || otp_ptr.is_null() {
+        return OOTP_ERR_NULL_POINTER;
+    }
+    let secret = slice::from_raw_parts(secret_ptr, secret_len);
+    let otp = slice::from_raw_parts(otp_ptr, otp_len);
+    guard(|| {
+        let totp = match ffi_totp(secret, digits, period, algo_id) {
+            Ok(totp) => totp,
+            Err(status) => return status,
+        };
+        let window = breadth_secs / totp.period;
+        if window > OOTP_MAX_WINDOW_STEPS {
+            return OOTP_ERR_INVALID_ARGUMENT;
+        }
+        match std::str::from_utf8(otp) {
+            Ok(otp) => totp.check(otp, Some(window)) as i32,
+            Err(_) => OOTP_ERR_INVALID_ARGUMENT,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        guard, ootp_totp_check, ootp_totp_make, OOTP_ERR_BUFFER_TOO_SMALL,
+        OOTP_ERR_INVALID_ARGUMENT, OOTP_ERR_NULL_POINTER, OOTP_ERR_PANIC, OOTP_MAX_WINDOW_STEPS,
+        OOTP_OK,
+    };
+    use crate::algorithm::OtpAlgorithmId;
+    use crate::totp::{CreateOption, Totp};
+    use std::ptr;
+
+    const SECRET: &[u8] = b"12345678901234567890123456789012";
+
+    fn ffi_make(digits: u32, algo_id: u8) -> Result<String, i32> {
+        let mut out = [0xffu8; 16];
+        let status = unsafe {
+            ootp_totp_make(
+                SECRET.as_ptr(),
+                SECRET.len(),
+                digits,
+                30,
+                algo_id,
+                out.as_mut_ptr(),
+                out.len(),
+            )
+        };
+        if status != OOTP_OK {
+            return Err(status);
+        }
+        let end = out.iter().position(|&byte| byte == 0).unwrap();
+        Ok(String::from_utf8(out[..end].to_vec()).unwrap())
+    }
+
+    fn ffi_check(otp: &[u8], algo_id: u8, breadth_secs: u64) -> i32 {
+        unsafe {
+            ootp_totp_check(
+                SECRET.as_ptr(),
+                SECRET.len(),
+                8,
+                30,
+                algo_id,
+                otp.as_ptr(),
+                otp.len(),
+                breadth_secs,
+            )
+        }
+    }
+
+    #[test]
+    fn ffi_parity_test() {
+        for algo_id in 0..=4 {
+            let totp = Totp::secret(
+                SECRET.to_vec(),
+                CreateOption::Full {
+                    digits: 8,
+                    period: 30,
+                    algorithm: OtpAlgorithmId::from_u8(algo_id).unwrap().sha_type(),
+                },
+            );
+            // Retry if a time step boundary was crossed between the calls.
+            let otp = loop {
+                let before = totp.make();
+                let otp = ffi_make(8, algo_id).unwrap();
+                if before == totp.make() {
+                    assert_eq!(otp, before);
+                    break otp;
+                }
+            };
+            assert_eq!(ffi_check(otp.as_bytes(), algo_id, 0), 1);
+            assert!(totp.check(&otp, Some(0)));
+        }
+        assert_eq!(ffi_check(b"not a code", 0, 30), 0);
+    }
+
+    #[test]
+    fn ffi_breadth_test() {
+        let totp = Totp::secret(SECRET.to_vec(), CreateOption::Digits(8));
+        // Retry if a time step boundary was crossed between the calls.
+        loop {
+            let (previous, older) = (totp.make_drift(-1), totp.make_drift(-2));
+            let statuses = [
+                ffi_check(previous.as_bytes(), 0, 29),
+                ffi_check(previous.as_bytes(), 0, 30),
+                ffi_check(older.as_bytes(), 0, 59),
+                ffi_check(older.as_bytes(), 0, 60),
+            ];
+            if previous == totp.make_drift(-1) {
+                assert_eq!(statuses, [0, 1, 0, 1]);
+                break;
+            }
+        }
+        let otp = totp.make();
+        assert_eq!(
+            ffi_check(otp.as_bytes(), 0, OOTP_MAX_WINDOW_STEPS * 30 + 29),
+            1
+        );
+        assert_eq!(
+            ffi_check(otp.as_bytes(), 0, (OOTP_MAX_WINDOW_STEPS + 1) * 30),
+            OOTP_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            ffi_check(b"00000000", 0, u64::MAX),
+            OOTP_ERR_INVALID_ARGUMENT
+        );
+    }
+
+    #[test]
+    fn ffi_errors_test() {
+        assert_eq!(ffi_make(8, 5), Err(OOTP_ERR_INVALID_ARGUMENT));
+        assert_eq!(ffi_make(0, 0), Err(OOTP_ERR_INVALID_ARGUMENT));
+        assert_eq!(ffi_make(11, 0), Err(OOTP_ERR_INVALID_ARGUMENT));
+        assert_eq!(ffi_check(b"\xff\xfe", 0, 30), OOTP_ERR_INVALID_ARGUMENT);
+        let mut out = [0u8; 6];
+        unsafe {
+            assert_eq!(
+                ootp_totp_make(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, out.as_mut_ptr(), 6),
+                OOTP_ERR_BUFFER_TOO_SMALL
+            );
+            assert_eq!(
+                ootp_totp_make(SECRET.as_ptr(), SECRET.len(), 6, 0, 0, out.as_mut_ptr(), 6),
+                OOTP_ERR_INVALID_ARGUMENT
+            );
+            assert_eq!(
+                ootp_totp_make(ptr::null(), 0, 6, 30, 0, out.as_mut_ptr(), 6),
+                OOTP_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                ootp_totp_make(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, ptr::null_mut(), 0),
+                OOTP_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                ootp_totp_check(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, ptr::null(), 0, 30),
+                OOTP_ERR_NULL_POINTER
+            );
+        }
+        assert_eq!(out, [0u8; 6]);
+        assert_eq!(guard(|| panic!("boom")), OOTP_ERR_PANIC);
+    }
+}
@@ -1,28 +1,391 @@
-use crate::constants::{DEFAULT_ALGORITHM, DEFAULT_BREADTH, DEFAULT_COUNTER, DEFAULT_DIGITS};
+use crate::constants::{
+    DEFAULT_ALGORITHM, DEFAULT_BREADTH, DEFAULT_COUNTER, DEFAULT_COUNTER_LENGTH, DEFAULT_DIGITS,
+    MAX_DIGITS, MAX_OTP_INPUT_LEN,
+};
+use crate::encoding::encode_base32;
 use hmacsha::{HmacSha, ShaTypes};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-/// Convert a `u64` value to an array of 8 elements of 8-bit.
-const fn u64_to_8_length_u8_array(input: u64) -> [u8; 8] {
-    input.to_be_bytes()
+/**
+Returns the 8-byte big-endian message the HMAC is computed over for `counter`,
+e.g. to cross-check intermediate values against other implementations.
+
+# Example
+
+```
+use ootp::hotp::counter_message;
+
+assert_eq!(counter_message(1), [0, 0, 0, 0, 0, 0, 0, 1]);
+```
+*/
+pub const fn counter_message(counter: u64) -> [u8; 8] {
+    counter.to_be_bytes()
+}
+
+/// Non-standard deviations from RFC 4226, to interoperate with legacy tokens and servers.
+///
+/// The default value follows the RFC. Codes computed with any other value are not
+/// accepted by standard implementations: only use it to match a specific peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interop {
+    /// **Legacy Java bug compatibility.** Some buggy Java servers derived from the RFC
+    /// reference implementation omit the `0x7f` mask of the dynamic truncation and reduce
+    /// the resulting signed 32-bit value, so that a digest whose offset byte has its high
+    /// bit set yields a negative code such as `"-728424"`.
+    ///
+    /// **Warning:** such codes are wrong per RFC 4226 and half as many values are possible
+    /// per sign. Never enable this unless the peer is known to have this bug.
+    pub legacy_java_unmasked: bool,
+    /// The length, in bytes, of the big-endian counter message, `8` per the RFC.
+    /// Some pre-standard tokens use a 4-byte message: shorter lengths keep the least
+    /// significant bytes of the counter, longer ones left-pad it with zeros.
+    pub counter_len: usize,
+    /// Where the truncation reads the 4 bytes of the digest from, `DynamicOffset` per the RFC.
+    pub offset: OffsetSource,
+}
+
+impl Default for Interop {
+    fn default() -> Self {
+        Self {
+            legacy_java_unmasked: false,
+            counter_len: DEFAULT_COUNTER_LENGTH,
+            offset: OffsetSource::DynamicOffset,
+        }
+    }
+}
+
+/// The offset of the 4 digest bytes kept by the truncation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffsetSource {
+    /// The low nibble of the last digest byte, the RFC 4226 dynamic truncation.
+    DynamicOffset,
+    /// A fixed offset, as used by some FIPS-variant tokens. This is non-standard.
+    /// An offset past the end of the digest yields the value `0`.
+    FixedOffset(usize),
+}
+
+/**
+Returns the smallest secret length, in bytes, providing at least `target_bits` bits of
+entropy, i.e. `ceil(target_bits / 8)`, e.g. to enforce a provisioning policy.
+RFC 4226 requires at least 128 bits and recommends 160.
+
+# Example
+
+```
+use ootp::hotp::min_secret_bytes_for_bits;
+
+assert_eq!(min_secret_bytes_for_bits(160.0), 20);
+```
+*/
+pub fn min_secret_bytes_for_bits(target_bits: f64) -> usize {
+    // The cast saturates, and maps NaN and negative values to 0.
+    (target_bits / 8.0).ceil() as usize
+}
+
+/// Returns the big-endian `counter` truncated or left-padded with zeros to `len` bytes.
+fn counter_message_len(counter: u64, len: usize) -> Vec<u8> {
+    let bytes = counter_message(counter);
+    let mut message = vec![0; len.saturating_sub(bytes.len())];
+    message.extend_from_slice(&bytes[bytes.len().saturating_sub(len)..]);
+    message
+}
+
+fn make_opt(secret: &[u8], salt: &[u8], digits: u32, counter: u64, algorithm: &ShaTypes) -> String {
+    format_code(truncate(secret, salt, counter, algorithm), digits)
+}
+
+/// Like `make_opt`, deviating from the RFC as requested by `interop`.
+fn make_interop_opt(
+    secret: &[u8],
+    digits: u32,
+    counter: u64,
+    algorithm: &ShaTypes,
+    salt: &[u8],
+    interop: &Interop,
+) -> String {
+    let message = salted_message(
+        &counter_message_len(counter, interop.counter_len),
+        salt,
+        b"",
+    );
+    with_message_digest(secret, &message, algorithm, |digest| {
+        if interop.legacy_java_unmasked {
+            format_legacy_java(unmasked_truncation(digest, interop.offset), digits)
+        } else {
+            format_code(truncation(digest, interop.offset), digits)
+        }
+    })
 }
 
-fn make_opt(secret: &[u8], digits: u32, counter: u64, algorithm: &ShaTypes) -> String {
-    let counter_bytes = u64_to_8_length_u8_array(counter);
-    let mut hash = HmacSha::new(secret, &counter_bytes, algorithm);
-    let digest = hash.compute_digest();
-    let offset = usize::from(digest.last().unwrap() & 0xf);
-    let value = (u32::from(digest[offset]) & 0x7f) << 24
-        | (u32::from(digest[offset + 1]) & 0xff) << 16
-        | (u32::from(digest[offset + 2]) & 0xff) << 8
-        | (u32::from(digest[offset + 3]) & 0xff);
-    let mut code = (value % 10_u32.pow(digits)).to_string();
+/// Returns the HMAC message of the counter bytes `counter`, `salt` and `pepper`.
+///
+/// Without a salt, this is the counter followed by the pepper, the RFC 4226 message when
+/// the pepper is empty too. With a salt, the salt and the pepper each follow as their
+/// 8-byte big-endian length and bytes, so that distinct pairs never yield the same message.
+pub(crate) fn salted_message(counter: &[u8], salt: &[u8], pepper: &[u8]) -> Vec<u8> {
+    if salt.is_empty() {
+        return [counter, pepper].concat();
+    }
+    let mut message = Vec::with_capacity(counter.len() + salt.len() + pepper.len() + 16);
+    message.extend_from_slice(counter);
+    for component in [salt, pepper].iter() {
+        message.extend_from_slice(&(component.len() as u64).to_be_bytes());
+        message.extend_from_slice(component);
+    }
+    message
+}
 
-    // Check whether the code is digits bits long, if not, use "0" to fill in the front
-    if code.len() != (digits as usize) {
-        code = "0".repeat((digits - (code.len() as u32)) as usize) + &code;
+/// Computes the HMAC of the counter salted with `salt` and passes the digest to `f`.
+fn with_digest<T>(
+    secret: &[u8],
+    salt: &[u8],
+    counter: u64,
+    algorithm: &ShaTypes,
+    f: impl FnOnce(&[u8]) -> T,
+) -> T {
+    if salt.is_empty() {
+        return with_message_digest(secret, &counter_message(counter), algorithm, f);
     }
+    let message = salted_message(&counter_message(counter), salt, b"");
+    with_message_digest(secret, &message, algorithm, f)
+}
+
+/// Computes the HMAC of `message` and passes the digest to `f`.
+fn with_message_digest<T>(
+    secret: &[u8],
+    message: &[u8],
+    algorithm: &ShaTypes,
+    f: impl FnOnce(&[u8]) -> T,
+) -> T {
+    f(&HmacSha::new(secret, message, algorithm).compute_digest())
+}
 
-    code
+/// Returns the counter, breadth and algorithm of `options`.
+fn check_params(options: CheckOption<'_>) -> (u64, u64, &ShaTypes) {
+    match options {
+        CheckOption::Default => (DEFAULT_COUNTER, DEFAULT_BREADTH, DEFAULT_ALGORITHM),
+        CheckOption::Counter(counter) => (counter, DEFAULT_BREADTH, DEFAULT_ALGORITHM),
+        CheckOption::Breadth(breadth) => (DEFAULT_COUNTER, breadth, DEFAULT_ALGORITHM),
+        CheckOption::Full {
+            counter,
+            breadth,
+            algorithm,
+        } => (counter, breadth, algorithm),
+        CheckOption::Algorithm(algorithm) => (DEFAULT_COUNTER, DEFAULT_BREADTH, algorithm),
+    }
+}
+
+/// Computes the HMAC of the salted counter and applies the RFC 4226 dynamic truncation.
+fn truncate(secret: &[u8], salt: &[u8], counter: u64, algorithm: &ShaTypes) -> u32 {
+    with_digest(secret, salt, counter, algorithm, dynamic_truncation)
+}
+
+/// Applies the truncation at the offset given by `source`, returning a 31-bit value.
+const fn truncation(digest: &[u8], source: OffsetSource) -> u32 {
+    match truncation_bytes(digest, source) {
+        Some(bytes) => u32::from_be_bytes(bytes) & 0x7fff_ffff,
+        None => 0,
+    }
+}
+
+/// Applies the truncation without the `0x7f` mask, as a signed 32-bit value.
+const fn unmasked_truncation(digest: &[u8], source: OffsetSource) -> i32 {
+    match truncation_bytes(digest, source) {
+        Some(bytes) => i32::from_be_bytes(bytes),
+        None => 0,
+    }
+}
+
+/// Formats a signed truncated value like Java's `%` and `Integer.toString`, left-padding
+/// the result, sign included, with "0" to `digits` characters, at most `MAX_DIGITS`.
+fn format_legacy_java(value: i32, digits: u32) -> String {
+    let value = match 10_i32.checked_pow(digits) {
+        Some(modulo) => value % modulo,
+        None => value,
+    };
+    let width = digits.min(MAX_DIGITS) as usize;
+    format!("{:0>width$}", value.to_string(), width = width)
+}
+
+/**
+Applies the RFC 4226 dynamic truncation to an HMAC `digest`, returning a 31-bit value.
+
+Only the HMAC computation is not `const`: the truncation and `code_value` can be
+evaluated at compile time, e.g. to embed verification tables from known digests.
+Returns `0` for a digest too short to be truncated.
+
+# Example
+
+```
+use ootp::hotp::{code_value, dynamic_truncation};
+
+// The HMAC-SHA-1 digest of RFC 4226 section 5.4.
+const DIGEST: [u8; 20] = [
+    0x1f, 0x86, 0x98, 0x69, 0x0e, 0x02, 0xca, 0x16, 0x61, 0x85,
+    0x50, 0xef, 0x7f, 0x19, 0xda, 0x8e, 0x94, 0x5b, 0x55, 0x5a,
+];
+const CODE: u32 = code_value(dynamic_truncation(&DIGEST), 6);
+assert_eq!(CODE, 872_921);
+```
+*/
+pub const fn dynamic_truncation(digest: &[u8]) -> u32 {
+    truncation(digest, OffsetSource::DynamicOffset)
+}
+
+/// Returns the 4 bytes of `digest` at the offset given by `source`,
+/// or `None` when they do not fit in the digest.
+const fn truncation_bytes(digest: &[u8], source: OffsetSource) -> Option<[u8; 4]> {
+    let offset = match source {
+        OffsetSource::DynamicOffset => match digest.last() {
+            Some(last) => (*last & 0xf) as usize,
+            None => return None,
+        },
+        OffsetSource::FixedOffset(offset) => offset,
+    };
+    if digest.len() < 4 || offset > digest.len() - 4 {
+        return None;
+    }
+    Some([
+        digest[offset],
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ])
+}
+
+/// Reduces a truncated `value` to its `digits` least significant decimal digits.
+///
+/// When `10^digits` does not fit in a `u32` the whole value is kept.
+pub const fn code_value(value: u32, digits: u32) -> u32 {
+    match 10_u32.checked_pow(digits) {
+        Some(modulo) => value % modulo,
+        None => value,
+    }
+}
+
+/// Formats a truncated value as a `digits` long code, left-padded with "0".
+///
+/// `digits` is clamped to `MAX_DIGITS`, padding beyond what a `u32` can fill being
+/// only zeros.
+pub(crate) fn format_code(value: u32, digits: u32) -> String {
+    format!(
+        "{:0>width$}",
+        code_value(value, digits),
+        width = digits.min(MAX_DIGITS) as usize
+    )
+}
+
+/// Formats a truncated value as a `digits` long code in base `radix`, left-padded with "0".
+///
+/// `radix` is clamped to `2..=36`, digits above 9 are lowercase letters. `digits` is
+/// clamped to `u32::BITS`, the length of `u32::MAX` in base 2.
+pub(crate) fn format_code_radix(value: u32, digits: u32, radix: u32) -> String {
+    let radix = radix.clamp(2, 36);
+    let mut value = radix
+        .checked_pow(digits)
+        .map_or(value, |modulo| value % modulo);
+    let digits = digits.min(u32::BITS);
+    let mut code = Vec::with_capacity(digits as usize);
+    loop {
+        code.push(std::char::from_digit(value % radix, radix).unwrap_or('0'));
+        value /= radix;
+        if value == 0 {
+            break;
+        }
+    }
+    code.resize(code.len().max(digits as usize), '0');
+    code.iter().rev().collect()
+}
+
+/// Returns the Luhn check digit of the decimal representation of `counter`.
+fn counter_check_digit(counter: u64) -> char {
+    let mut sum = 0;
+    let mut rest = counter;
+    let mut double = true;
+    loop {
+        let digit = rest % 10;
+        sum += match (double, digit * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => digit,
+        };
+        double = !double;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    char::from(b'0' + ((10 - sum % 10) % 10) as u8)
+}
+
+/// Strips the separators some tokens display inside a code ("123-456", "123.456", "123 456")
+/// and whitespace, such as the trailing newline of a code read from stdin,
+/// before the code is compared.
+///
+/// None of the stripped characters can be part of a generated code, so no valid
+/// code character is ever removed.
+///
+/// Inputs longer than `MAX_OTP_INPUT_LEN` bytes are not scanned and normalize to an empty
+/// string, which never matches a code.
+///
+/// With the `unicode-digits` feature, the Unicode decimal digits of common scripts, such as
+/// the full-width digits of some mobile keyboards, are also transliterated to ASCII.
+pub(crate) fn normalize(otp: &str) -> String {
+    if otp.len() > MAX_OTP_INPUT_LEN {
+        return String::new();
+    }
+    let chars = otp
+        .chars()
+        .filter(|&c| !(c.is_whitespace() || c == '-' || c == '.'));
+    #[cfg(feature = "unicode-digits")]
+    let chars = chars.map(|c| ascii_digit(c).unwrap_or(c));
+    chars.collect()
+}
+
+/// The first code point, i.e. the zero, of the Unicode decimal digit ranges recognized
+/// by `ascii_digit`.
+#[cfg(feature = "unicode-digits")]
+const UNICODE_ZEROS: [u32; 21] = [
+    0x0660, // Arabic-Indic
+    0x06F0, // Extended Arabic-Indic
+    0x07C0, // NKo
+    0x0966, // Devanagari
+    0x09E6, // Bengali
+    0x0A66, // Gurmukhi
+    0x0AE6, // Gujarati
+    0x0B66, // Oriya
+    0x0BE6, // Tamil
+    0x0C66, // Telugu
+    0x0CE6, // Kannada
+    0x0D66, // Malayalam
+    0x0DE6, // Sinhala Lith
+    0x0E50, // Thai
+    0x0ED0, // Lao
+    0x0F20, // Tibetan
+    0x1040, // Myanmar
+    0x17E0, // Khmer
+    0x1810, // Mongolian
+    0x1946, // Limbu
+    0xFF10, // Fullwidth
+];
+
+/// Returns the ASCII digit of a non-ASCII Unicode decimal digit, if recognized.
+#[cfg(feature = "unicode-digits")]
+fn ascii_digit(c: char) -> Option<char> {
+    let code = c as u32;
+    UNICODE_ZEROS
+        .iter()
+        .find(|&&zero| (zero..zero + 10).contains(&code))
+        .and_then(|&zero| char::from_digit(code - zero, 10))
+}
+
+/// Compares two byte strings in constant time with respect to their content.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 /// The Options for the HOTP `make` function.
@@ -65,14 +428,89 @@ pub enum CheckOption<'a> {
 
 /// The HOTP is a HMAC-based one-time password algorithm.
 ///
-/// It takes one parameter, the shared secret between client and server.
+/// It takes one parameter, the shared secret between client and server, and an optional
+/// per-account salt, see `with_salt`. Its `Debug` output redacts the secret.
 pub struct Hotp {
     secret: Vec<u8>,
+    salt: Vec<u8>,
+    /// Whether the secret is overwritten with zeros on drop, see `Totp::from_shares`.
+    #[cfg(feature = "sss")]
+    pub(crate) wipe_on_drop: bool,
+}
+
+#[cfg(feature = "sss")]
+impl Drop for Hotp {
+    fn drop(&mut self) {
+        if self.wipe_on_drop {
+            self.wipe_secret();
+        }
+    }
+}
+
+impl fmt::Debug for Hotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hotp")
+            .field("secret", &"REDACTED")
+            .field("salt", &self.salt)
+            .finish()
+    }
 }
 
 impl Hotp {
     pub const fn new(secret: Vec<u8>) -> Self {
-        Self { secret }
+        Self {
+            secret,
+            salt: Vec::new(),
+            #[cfg(feature = "sss")]
+            wipe_on_drop: false,
+        }
+    }
+
+    /**
+    HOTP instance constructor, copying `secret` from anything viewable as bytes:
+    `&[u8]`, arrays or RustCrypto `GenericArray`s.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+
+    let hotp = Hotp::from_bytes(b"12345678901234567890");
+    assert_eq!(hotp.make(MakeOption::Counter(0)), "755224");
+    ```
+    */
+    pub fn from_bytes(secret: impl AsRef<[u8]>) -> Self {
+        Self::new(secret.as_ref().to_vec())
+    }
+
+    /**
+    Sets the per-account salt appended, length-prefixed, to the 8-byte counter message
+    before the HMAC computation, so that codes cannot be precomputed for a leaked secret alone.
+
+    This is a non-standard extension: the salt is not secret and is stored alongside the
+    account, but both sides must use the same salt. It is exported as the non-standard
+    `salt` parameter of provisioning URIs and the `salt_base32` field of account
+    descriptors. An empty salt, the default, yields the RFC 4226 code.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let salted = Hotp::new("12345678901234567890".as_bytes().to_vec()).with_salt(b"account-42");
+    assert_ne!(salted.make(MakeOption::Counter(1)), hotp.make(MakeOption::Counter(1)));
+    assert_eq!(salted.salt(), b"account-42");
+    ```
+    */
+    pub fn with_salt(mut self, salt: impl AsRef<[u8]>) -> Self {
+        self.salt = salt.as_ref().to_vec();
+        self
+    }
+
+    /// Returns the per-account salt, empty by default. See `with_salt`.
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
     }
 
     /**
@@ -107,25 +545,38 @@ impl Hotp {
     pub fn make(&self, options: MakeOption) -> String {
         match options {
             MakeOption::Default => make_opt(
-                &self.secret(),
+                &self.secret,
+                &self.salt,
                 DEFAULT_DIGITS,
                 DEFAULT_COUNTER,
                 DEFAULT_ALGORITHM,
             ),
-            MakeOption::Counter(counter) => {
-                make_opt(&self.secret(), DEFAULT_DIGITS, counter, DEFAULT_ALGORITHM)
-            }
-            MakeOption::Digits(digits) => {
-                make_opt(&self.secret(), digits, DEFAULT_COUNTER, DEFAULT_ALGORITHM)
-            }
+            MakeOption::Counter(counter) => make_opt(
+                &self.secret,
+                &self.salt,
+                DEFAULT_DIGITS,
+                counter,
+                DEFAULT_ALGORITHM,
+            ),
+            MakeOption::Digits(digits) => make_opt(
+                &self.secret,
+                &self.salt,
+                digits,
+                DEFAULT_COUNTER,
+                DEFAULT_ALGORITHM,
+            ),
             MakeOption::Full {
                 counter,
                 digits,
                 algorithm,
-            } => make_opt(&self.secret(), digits, counter, algorithm),
-            MakeOption::Algorithm(algorithm) => {
-                make_opt(&self.secret(), DEFAULT_DIGITS, DEFAULT_COUNTER, algorithm)
-            }
+            } => make_opt(&self.secret, &self.salt, digits, counter, algorithm),
+            MakeOption::Algorithm(algorithm) => make_opt(
+                &self.secret,
+                &self.salt,
+                DEFAULT_DIGITS,
+                DEFAULT_COUNTER,
+                algorithm,
+            ),
         }
     }
     /**
@@ -150,20 +601,13 @@ impl Hotp {
     let check = hotp.check(code.as_str(), CheckOption::Counter(2));
     ```
     */
-
     pub fn check(&self, otp: &str, options: CheckOption) -> bool {
-        let (counter, breadth, algorithm) = match options {
-            CheckOption::Default => (DEFAULT_COUNTER, DEFAULT_BREADTH, DEFAULT_ALGORITHM),
-            CheckOption::Counter(counter) => (counter, DEFAULT_BREADTH, DEFAULT_ALGORITHM),
-            CheckOption::Breadth(breadth) => (DEFAULT_COUNTER, breadth, DEFAULT_ALGORITHM),
-            CheckOption::Full {
-                counter,
-                breadth,
-                algorithm,
-            } => (counter, breadth, algorithm),
-            CheckOption::Algorithm(algorithm) => (DEFAULT_COUNTER, DEFAULT_BREADTH, algorithm),
-        };
-        for i in (counter - breadth)..=(counter + breadth) {
+        let (counter, breadth, algorithm) = check_params(options);
+        let otp = normalize(otp);
+        if otp.is_empty() {
+            return false;
+        }
+        for i in counter.saturating_sub(breadth)..=counter.saturating_add(breadth) {
             let code = self.make(MakeOption::Full {
                 counter: i,
                 digits: otp.len() as u32,
@@ -176,18 +620,623 @@ impl Hotp {
         false
     }
 
+    /**
+    Returns a boolean indicating if `otp` is the code of a counter within the breadth of
+    `options`, as encoded by `encoder` from the 31-bit dynamic truncation value, e.g. for
+    custom encodings such as Steam, base62 or word lists.
+
+    The code is compared as is, without the normalization of `check`.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, CheckOption};
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let hex = |value: u32| format!("{:08x}", value);
+    assert!(hotp.check_with("41397eea", hex, CheckOption::Counter(1)));
+    ```
+    */
+    pub fn check_with<F: Fn(u32) -> String>(
+        &self,
+        otp: &str,
+        encoder: F,
+        options: CheckOption,
+    ) -> bool {
+        if otp.len() > MAX_OTP_INPUT_LEN {
+            return false;
+        }
+        let (counter, breadth, algorithm) = check_params(options);
+        (counter.saturating_sub(breadth)..=counter.saturating_add(breadth)).any(|i| {
+            let code = encoder(truncate(&self.secret, &self.salt, i, algorithm));
+            constant_time_eq(code.as_bytes(), otp.as_bytes())
+        })
+    }
+
+    /**
+    Returns the counter of `otp` if it is the one-time password of a counter within
+    `narrow` of `hint`, e.g. the counter following the last successful authentication,
+    or, failing that, within `wide` of `hint`. The common case thus computes few codes.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let otp = hotp.make(MakeOption::Counter(12));
+    assert_eq!(hotp.check_with_hint(&otp, 10, 1, 5, &ShaTypes::Sha1), Some(12));
+    assert_eq!(hotp.check_with_hint(&otp, 10, 1, 1, &ShaTypes::Sha1), None);
+    ```
+    */
+    pub fn check_with_hint(
+        &self,
+        otp: &str,
+        hint: u64,
+        narrow: u64,
+        wide: u64,
+        algorithm: &ShaTypes,
+    ) -> Option<u64> {
+        let otp = normalize(otp);
+        if otp.is_empty() {
+            return None;
+        }
+        let around = |breadth: u64| hint.saturating_sub(breadth)..=hint.saturating_add(breadth);
+        let matches = |&counter: &u64| {
+            let code = make_opt(
+                &self.secret,
+                &self.salt,
+                otp.len() as u32,
+                counter,
+                algorithm,
+            );
+            constant_time_eq(code.as_bytes(), otp.as_bytes())
+        };
+        around(narrow).find(matches).or_else(|| {
+            around(wide)
+                .filter(|counter| !around(narrow).contains(counter))
+                .find(matches)
+        })
+    }
+
+    /**
+    Returns a boolean indicating if `tagged`, a `counter:code` pair as sent by some
+    debugging protocols, holds the one-time password of exactly that counter.
+
+    A missing separator, a counter that is not a decimal `u64` or an empty code
+    are rejected.
+
+    # Example
+
+    ```
+    use ootp::hotp::Hotp;
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    assert!(hotp.check_tagged("3:969429", &ShaTypes::Sha1));
+    assert!(!hotp.check_tagged("x:969429", &ShaTypes::Sha1));
+    ```
+    */
+    pub fn check_tagged(&self, tagged: &str, algorithm: &ShaTypes) -> bool {
+        let (counter, otp) = match tagged.split_once(':') {
+            Some(pair) => pair,
+            None => return false,
+        };
+        let counter = match counter.trim().parse::<u64>() {
+            Ok(counter) => counter,
+            Err(_) => return false,
+        };
+        let otp = normalize(otp);
+        if otp.is_empty() {
+            return false;
+        }
+        let code = make_opt(
+            &self.secret,
+            &self.salt,
+            otp.len() as u32,
+            counter,
+            algorithm,
+        );
+        constant_time_eq(code.as_bytes(), otp.as_bytes())
+    }
+
+    /**
+    Returns the one-time password of `counter` written with `digits` digits in base `radix`
+    (`2..=36`, lowercase letters above 9), at most `u32::BITS` digits.
+
+    This is a non-standard extension: RFC 4226 codes are decimal, i.e. `radix` 10.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+    let decimal = hotp.make_radix(42, 6, 10, &ShaTypes::Sha1);
+    assert_eq!(decimal, hotp.make(MakeOption::Counter(42)));
+    let hexadecimal = hotp.make_radix(42, 6, 16, &ShaTypes::Sha1);
+    assert!(hexadecimal.chars().all(|c| c.is_digit(16)));
+    ```
+    */
+    pub fn make_radix(
+        &self,
+        counter: u64,
+        digits: u32,
+        radix: u32,
+        algorithm: &ShaTypes,
+    ) -> String {
+        format_code_radix(
+            truncate(&self.secret, &self.salt, counter, algorithm),
+            digits,
+            radix,
+        )
+    }
+
+    /**
+    Returns the one-time password of `counter` with `pepper` appended to the 8-byte
+    counter message before the HMAC computation.
+
+    This is a non-standard extension: the pepper is an extra server-side secret that
+    both sides must share, so that a leaked `secret` alone is not enough to compute codes.
+    An empty pepper yields the RFC 4226 code.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+    let otp = hotp.make_peppered(1, b"pepper", 6, &ShaTypes::Sha1);
+    assert!(hotp.check_peppered(&otp, 1, 0, b"pepper", &ShaTypes::Sha1));
+    assert_eq!(hotp.make_peppered(1, b"", 6, &ShaTypes::Sha1), hotp.make(MakeOption::Counter(1)));
+    ```
+    */
+    pub fn make_peppered(
+        &self,
+        counter: u64,
+        pepper: &[u8],
+        digits: u32,
+        algorithm: &ShaTypes,
+    ) -> String {
+        let message = salted_message(&counter_message(counter), &self.salt, pepper);
+        with_message_digest(&self.secret, &message, algorithm, |digest| {
+            format_code(dynamic_truncation(digest), digits)
+        })
+    }
+
+    /// Returns a boolean indicating if `otp` is the peppered one-time password of a counter
+    /// within `breadth` of `counter`. See `make_peppered`.
+    pub fn check_peppered(
+        &self,
+        otp: &str,
+        counter: u64,
+        breadth: u64,
+        pepper: &[u8],
+        algorithm: &ShaTypes,
+    ) -> bool {
+        let otp = normalize(otp);
+        if otp.is_empty() {
+            return false;
+        }
+        (counter.saturating_sub(breadth)..=counter.saturating_add(breadth)).any(|i| {
+            let code = self.make_peppered(i, pepper, otp.len() as u32, algorithm);
+            constant_time_eq(code.as_bytes(), otp.as_bytes())
+        })
+    }
+
+    /**
+    Returns the one-time password of `counter` followed by a check digit computed over the
+    counter value itself, not the code, so that a relying party can detect a counter
+    desynchronization from the last character alone.
+
+    **This scheme is not standard:** the check digit is the Luhn check digit of the decimal
+    counter, unlike the RFC 4226 suggestion of a Luhn digit over the code.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let otp = hotp.make_with_counter_check(1, 6, &ShaTypes::Sha1);
+    assert_eq!(otp, "2870828");
+    assert!(hotp.check_with_counter_check(&otp, 1, 0, &ShaTypes::Sha1));
+    ```
+    */
+    pub fn make_with_counter_check(
+        &self,
+        counter: u64,
+        digits: u32,
+        algorithm: &ShaTypes,
+    ) -> String {
+        let mut otp = make_opt(&self.secret, &self.salt, digits, counter, algorithm);
+        otp.push(counter_check_digit(counter));
+        otp
+    }
+
+    /// Returns a boolean indicating if `otp` is the `make_with_counter_check` code of
+    /// a counter within `breadth` of `counter`, rejecting codes with a mismatched check digit.
+    pub fn check_with_counter_check(
+        &self,
+        otp: &str,
+        counter: u64,
+        breadth: u64,
+        algorithm: &ShaTypes,
+    ) -> bool {
+        let otp = normalize(otp);
+        let digits = match otp.len().checked_sub(1) {
+            Some(digits) if digits > 0 => digits as u32,
+            _ => return false,
+        };
+        (counter.saturating_sub(breadth)..=counter.saturating_add(breadth)).any(|i| {
+            let code = self.make_with_counter_check(i, digits, algorithm);
+            constant_time_eq(code.as_bytes(), otp.as_bytes())
+        })
+    }
+
+    /**
+    Returns the combination of the HOTP code of `hotp_counter` followed by the TOTP code
+    of `time`, in seconds since the UNIX epoch, with `period`, both `digits` long, for
+    schemes chaining an event factor and a time factor.
+
+    **This scheme is not standard:** the combined code is the plain concatenation of the
+    two codes, `2 * digits` long, the HOTP half first.
+
+    # Example
+
+    ```
+    use ootp::clock::{SystemClock, TimeSource};
+    use ootp::hotp::{Hotp, MakeOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+    let now = SystemClock.now_secs();
+    let otp = hotp.make_combined(7, 30, 6, &ShaTypes::Sha1, now);
+    assert_eq!(otp[..6], hotp.make(MakeOption::Counter(7)));
+    assert!(hotp.check_combined(&otp, 7, 30, 6, &ShaTypes::Sha1, 1, now));
+    ```
+    */
+    pub fn make_combined(
+        &self,
+        hotp_counter: u64,
+        period: u64,
+        digits: u32,
+        algorithm: &ShaTypes,
+        time: u64,
+    ) -> String {
+        let event = make_opt(&self.secret, &self.salt, digits, hotp_counter, algorithm);
+        let time = make_opt(
+            &self.secret,
+            &self.salt,
+            digits,
+            time / period.max(1),
+            algorithm,
+        );
+        event + &time
+    }
+
+    /// Returns a boolean indicating if `otp` is the `make_combined` code of `hotp_counter`,
+    /// with its TOTP half valid within `window` steps of `time`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn check_combined(
+        &self,
+        otp: &str,
+        hotp_counter: u64,
+        period: u64,
+        digits: u32,
+        algorithm: &ShaTypes,
+        window: u64,
+        time: u64,
+    ) -> bool {
+        let otp = normalize(otp);
+        let digits_len = digits as usize;
+        if otp.len() != 2 * digits_len || !otp.is_char_boundary(digits_len) {
+            return false;
+        }
+        let (event, time_code) = otp.split_at(digits_len);
+        let step = time / period.max(1);
+        let event_code = make_opt(&self.secret, &self.salt, digits, hotp_counter, algorithm);
+        let time_matched = (step.saturating_sub(window)..=step.saturating_add(window)).fold(
+            false,
+            |matched, counter| {
+                let code = make_opt(&self.secret, &self.salt, digits, counter, algorithm);
+                matched | constant_time_eq(code.as_bytes(), time_code.as_bytes())
+            },
+        );
+        constant_time_eq(event_code.as_bytes(), event.as_bytes()) & time_matched
+    }
+
+    /**
+    Returns the one-time password of `counter`, deviating from RFC 4226 as requested by
+    `interop` to match a non-conforming peer. See `Interop` for the available deviations.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, Interop};
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let interop = Interop { legacy_java_unmasked: true, ..Interop::default() };
+    assert_eq!(hotp.make_interop(0, 6, &ShaTypes::Sha1, &interop), "-728424");
+    let interop = Interop { counter_len: 4, ..Interop::default() };
+    assert_eq!(hotp.make_interop(1, 6, &ShaTypes::Sha1, &interop), "675152");
+    ```
+    */
+    pub fn make_interop(
+        &self,
+        counter: u64,
+        digits: u32,
+        algorithm: &ShaTypes,
+        interop: &Interop,
+    ) -> String {
+        make_interop_opt(
+            &self.secret,
+            digits,
+            counter,
+            algorithm,
+            &self.salt,
+            interop,
+        )
+    }
+
+    /**
+    Returns the one-time passwords of the given `counter` for each of the `digit_widths`,
+    computing the HMAC only once.
+
+    # Example
+
+    ```
+    use ootp::hotp::Hotp;
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+    let codes = hotp.make_multi(42, &[6, 8], &ShaTypes::Sha1);
+    assert_eq!(codes[0].0, 6);
+    assert_eq!(codes[1].1.len(), 8);
+    ```
+    */
+    pub fn make_multi(
+        &self,
+        counter: u64,
+        digit_widths: &[u32],
+        algorithm: &ShaTypes,
+    ) -> Vec<(u32, String)> {
+        let value = truncate(&self.secret, &self.salt, counter, algorithm);
+        digit_widths
+            .iter()
+            .map(|&digits| (digits, format_code(value, digits)))
+            .collect()
+    }
+
+    /**
+    Returns the `(counter, code)` pairs of the given `counters`, e.g. as test vectors
+    for downstream test suites. See `format_vector_table` to embed them as test data.
+
+    # Example
+
+    ```
+    use ootp::hotp::Hotp;
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let table = hotp.vector_table(&[0, 1], 6, &ShaTypes::Sha1);
+    assert_eq!(table, [(0, "755224".to_string()), (1, "287082".to_string())]);
+    ```
+    */
+    pub fn vector_table(
+        &self,
+        counters: &[u64],
+        digits: u32,
+        algorithm: &ShaTypes,
+    ) -> Vec<(u64, String)> {
+        counters
+            .iter()
+            .map(|&counter| {
+                (
+                    counter,
+                    make_opt(&self.secret, &self.salt, digits, counter, algorithm),
+                )
+            })
+            .collect()
+    }
+
+    /**
+    Returns a lazy iterator over the one-time passwords of the counters `start`, `start + 1`, ...
+
+    # Example
+
+    ```
+    use ootp::hotp::Hotp;
+    use ootp::hmacsha::ShaTypes;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let codes: Vec<String> = hotp.counter_iter(0, 6, &ShaTypes::Sha1).take(2).collect();
+    assert_eq!(codes, ["755224", "287082"]);
+    ```
+    */
+    pub fn counter_iter<'b>(
+        &'b self,
+        start: u64,
+        digits: u32,
+        algorithm: &'b ShaTypes,
+    ) -> impl Iterator<Item = String> + 'b {
+        (start..=u64::MAX)
+            .map(move |counter| make_opt(&self.secret, &self.salt, digits, counter, algorithm))
+    }
+
     /// Get a reference to the hotp's  secret.
     pub fn secret(&self) -> Vec<u8> {
         self.secret.clone()
     }
+
+    /// Overwrites the secret with zeros, e.g. before dropping a short-lived copy.
+    #[cfg(any(feature = "encrypted-secret", feature = "sss"))]
+    pub(crate) fn wipe_secret(&mut self) {
+        crate::wipe::wipe(&mut self.secret);
+    }
+
+    /// Makes the secret be overwritten with zeros when this `Hotp` is dropped.
+    #[cfg(feature = "sss")]
+    pub(crate) fn wipe_on_drop(mut self) -> Self {
+        self.wipe_on_drop = true;
+        self
+    }
+
+    /**
+    Returns the secret as an unpadded, uppercase RFC 4648 Base32 string, as entered in
+    authenticator apps.
+
+    # Example
+
+    ```
+    use ootp::hotp::Hotp;
+
+    let hotp = Hotp::new(b"Hello!\xDE\xAD\xBE\xEF".to_vec());
+    assert_eq!(hotp.secret_base32(), "JBSWY3DPEHPK3PXP");
+    ```
+    */
+    pub fn secret_base32(&self) -> String {
+        encode_base32(&self.secret)
+    }
+
+    /// Returns the salt as an unpadded, uppercase Base32 string, empty without a salt.
+    pub fn salt_base32(&self) -> String {
+        encode_base32(&self.salt)
+    }
+}
+
+/**
+Formats a `Hotp::vector_table` as the lines of a Rust array of `(counter, code)` tuples,
+ready to be embedded as test data.
+
+# Example
+
+```
+use ootp::hotp::{format_vector_table, Hotp};
+use ootp::hmacsha::ShaTypes;
+
+let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+let table = hotp.vector_table(&[0, 1], 6, &ShaTypes::Sha1);
+assert_eq!(format_vector_table(&table), "(0, \"755224\"),\n(1, \"287082\"),\n");
+```
+*/
+pub fn format_vector_table(table: &[(u64, String)]) -> String {
+    table
+        .iter()
+        .map(|(counter, code)| format!("({}, {:?}),\n", counter, code))
+        .collect()
+}
+
+/// A HOTP generator holding its own counter in an `AtomicU64`.
+///
+/// Every call to `next` consumes a unique counter, so several threads can share one
+/// `AtomicHotp` without external locking. The counter lives in memory only: it does not
+/// coordinate across processes, which must share the counter through their own storage.
+pub struct AtomicHotp<'a> {
+    hotp: Hotp,
+    counter: AtomicU64,
+    digits: u32,
+    algorithm: &'a ShaTypes,
+}
+
+impl<'a> AtomicHotp<'a> {
+    /// Creates a generator whose first code uses `counter`.
+    pub const fn new(secret: Vec<u8>, counter: u64, digits: u32, algorithm: &'a ShaTypes) -> Self {
+        Self {
+            hotp: Hotp::new(secret),
+            counter: AtomicU64::new(counter),
+            digits,
+            algorithm,
+        }
+    }
+
+    /// Like `new`, copying `secret` from anything viewable as bytes.
+    pub fn from_bytes(
+        secret: impl AsRef<[u8]>,
+        counter: u64,
+        digits: u32,
+        algorithm: &'a ShaTypes,
+    ) -> Self {
+        Self::new(secret.as_ref().to_vec(), counter, digits, algorithm)
+    }
+
+    /**
+    Returns the one-time password for the next counter, advancing the counter atomically.
+
+    # Example
+
+    ```
+    use ootp::hotp::{AtomicHotp, Hotp, MakeOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let hotp = AtomicHotp::new(secret.clone(), 0, 6, &ShaTypes::Sha1);
+    assert_eq!(hotp.next(), Hotp::new(secret).make(MakeOption::Counter(0)));
+    ```
+    */
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&self) -> String {
+        self.next_with_counter().1
+    }
+
+    /// Like `next`, but also returns the counter the one-time password was made with.
+    pub fn next_with_counter(&self) -> (u64, String) {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+        let code = self.hotp.make(MakeOption::Full {
+            counter,
+            digits: self.digits,
+            algorithm: self.algorithm,
+        });
+        (counter, code)
+    }
+
+    /// Returns the counter the next one-time password will be made with.
+    pub fn counter(&self) -> u64 {
+        self.counter.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use hmacsha::ShaTypes;
 
-    use super::{u64_to_8_length_u8_array, CheckOption, Hotp, MakeOption};
-    use crate::constants::DEFAULT_ALGORITHM;
+    use super::{
+        code_value, counter_check_digit, counter_message, counter_message_len, dynamic_truncation,
+        format_code, format_code_radix, format_legacy_java, format_vector_table,
+        min_secret_bytes_for_bits, normalize, salted_message, truncation, unmasked_truncation,
+        AtomicHotp, CheckOption, Hotp, Interop, MakeOption, OffsetSource,
+    };
+    use crate::constants::{DEFAULT_ALGORITHM, MAX_DIGITS, MAX_OTP_INPUT_LEN};
+
+    #[test]
+    fn from_bytes_test() {
+        let secret = b"12345678901234567890";
+        let expected = Hotp::new(secret.to_vec()).make(MakeOption::Counter(1));
+        assert_eq!(expected, "287082");
+        let array =
+            generic_array::GenericArray::<u8, generic_array::typenum::U20>::clone_from_slice(
+                secret,
+            );
+        let hotps = [
+            Hotp::from_bytes(secret),
+            Hotp::from_bytes(*secret),
+            Hotp::from_bytes(&secret[..]),
+            Hotp::from_bytes(String::from_utf8(secret.to_vec()).unwrap()),
+            Hotp::from_bytes("12345678901234567890"),
+            Hotp::from_bytes(array),
+        ];
+        for hotp in hotps.iter() {
+            assert_eq!(hotp.secret(), secret);
+            assert_eq!(hotp.make(MakeOption::Counter(1)), expected);
+        }
+        let atomic = AtomicHotp::from_bytes(&secret[..], 1, 6, DEFAULT_ALGORITHM);
+        assert_eq!(atomic.next(), expected);
+    }
 
     #[test]
     fn make_test() {
@@ -238,6 +1287,53 @@ mod tests {
         assert_eq!(code, "520489");
     }
 
+    /// Taken from [RFC 4226](https://datatracker.ietf.org/doc/html/rfc4226#appendix-D)
+    #[test]
+    fn counter_iter_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let codes: Vec<String> = hotp
+            .counter_iter(0, 6, DEFAULT_ALGORITHM)
+            .take(10)
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+                "399871", "520489",
+            ]
+        );
+        let mut codes = hotp.counter_iter(u64::MAX, 6, DEFAULT_ALGORITHM);
+        assert!(codes.next().is_some());
+        assert!(codes.next().is_none());
+    }
+
+    /// Secrets exactly as long as the HMAC block size are used as is, longer ones are
+    /// hashed first (RFC 2104). Expected codes computed with Python's `hmac` module.
+    #[test]
+    fn make_test_block_size_secret() {
+        let make = |secret: &[u8], counter, algorithm| {
+            Hotp::new(secret.to_vec()).make(MakeOption::Full {
+                counter,
+                digits: 8,
+                algorithm,
+            })
+        };
+        let secret = "1234567890".repeat(13);
+        let block_64 = &secret.as_bytes()[..64];
+        let block_128 = &secret.as_bytes()[..128];
+        assert_eq!(make(block_64, 0, &ShaTypes::Sha2_256), "75218771");
+        assert_eq!(make(block_64, 1, &ShaTypes::Sha2_256), "73786473");
+        assert_eq!(make(block_64, 2, &ShaTypes::Sha2_256), "08693748");
+        assert_eq!(make(block_64, 3, &ShaTypes::Sha2_256), "07403299");
+        assert_eq!(make(block_64, 0, &ShaTypes::Sha1), "87514304");
+        assert_eq!(make(block_64, 1, &ShaTypes::Sha1), "14779409");
+        assert_eq!(make(block_128, 0, &ShaTypes::Sha2_512), "82743649");
+        assert_eq!(make(block_128, 1, &ShaTypes::Sha2_512), "08262687");
+        let over_block = &secret.as_bytes()[..65];
+        assert_eq!(make(over_block, 0, &ShaTypes::Sha2_256), "00671230");
+        assert_eq!(make(over_block, 1, &ShaTypes::Sha2_256), "36516488");
+    }
+
     #[test]
     fn check_test() {
         let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
@@ -293,17 +1389,543 @@ mod tests {
     }
 
     #[test]
-    fn check_u64_to_8_length_u8_array() {
+    fn make_multi_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let codes = hotp.make_multi(7, &[6, 8], DEFAULT_ALGORITHM);
+        let code6 = hotp.make(MakeOption::Full {
+            counter: 7,
+            digits: 6,
+            algorithm: DEFAULT_ALGORITHM,
+        });
+        let code8 = hotp.make(MakeOption::Full {
+            counter: 7,
+            digits: 8,
+            algorithm: DEFAULT_ALGORITHM,
+        });
+        assert_eq!(codes, vec![(6, code6), (8, code8)]);
+        assert_eq!(codes[0].1, "162583");
+    }
+
+    #[test]
+    fn vector_table_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let counters: Vec<u64> = (0..=9).collect();
+        let table = hotp.vector_table(&counters, 6, DEFAULT_ALGORITHM);
+        // RFC 4226 appendix D.
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        assert_eq!(table.len(), expected.len());
+        for (counter, ((row_counter, code), expected)) in table.iter().zip(expected).enumerate() {
+            assert_eq!(*row_counter, counter as u64);
+            assert_eq!(code, expected);
+        }
+        let formatted = format_vector_table(&table[..2]);
+        assert_eq!(formatted, "(0, \"755224\"),\n(1, \"287082\"),\n");
+        assert!(hotp.vector_table(&[], 6, DEFAULT_ALGORITHM).is_empty());
+    }
+
+    #[test]
+    fn check_test_separators() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        assert!(hotp.check("755224", CheckOption::Default));
+        assert!(hotp.check("755-224", CheckOption::Default));
+        assert!(hotp.check("75\n5224", CheckOption::Default));
+        assert!(hotp.check("755.224\r\n", CheckOption::Default));
+        assert!(!hotp.check("755_224", CheckOption::Default));
+    }
+
+    #[test]
+    fn check_test_unicode_digits() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let full_width = "\u{ff17}\u{ff15}\u{ff15}\u{ff12}\u{ff12}\u{ff14}";
+        let arabic_indic = "\u{0667}\u{0665}\u{0665}\u{0662}\u{0662}\u{0664}";
+        let accepted = cfg!(feature = "unicode-digits");
+        assert_eq!(hotp.check(full_width, CheckOption::Default), accepted);
+        assert_eq!(hotp.check(arabic_indic, CheckOption::Default), accepted);
+        assert_eq!(
+            normalize("\u{ff11}\u{ff12}\u{ff13}\u{ff14}\u{ff15}\u{ff16}") == "123456",
+            accepted
+        );
+        assert!(!hotp.check("\u{ff17}55224x", CheckOption::Default));
+    }
+
+    #[test]
+    fn check_test_trailing_newline() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        assert!(hotp.check("755224\n", CheckOption::Default));
+        assert!(hotp.check("755224\r\n", CheckOption::Default));
+    }
+
+    #[test]
+    fn oversized_input_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        // A valid code padded with whitespace, which normalization would otherwise strip.
+        let padded = format!("755224{}", " ".repeat(10_000));
+        assert_eq!(normalize(&padded).capacity(), 0);
+        assert!(!hotp.check(&padded, CheckOption::Default));
+        assert!(!hotp.check_tagged(&format!("0:{}", padded), &ShaTypes::Sha1));
+        assert!(!hotp.check_peppered(&padded, 0, 0, b"", &ShaTypes::Sha1));
+        let long = "7".repeat(10_000);
+        assert!(!hotp.check_with(&long, |_| long.clone(), CheckOption::Default));
+        let limit = format!("755224{}", " ".repeat(MAX_OTP_INPUT_LEN - 6));
+        assert!(hotp.check(&limit, CheckOption::Default));
+        assert!(!hotp.check(&format!("{} ", limit), CheckOption::Default));
+    }
+
+    #[test]
+    fn atomic_next_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let hotp = AtomicHotp::new(secret, 0, 6, DEFAULT_ALGORITHM);
+        assert_eq!(hotp.next(), "755224");
+        assert_eq!(hotp.next(), "287082");
+        assert_eq!(hotp.counter(), 2);
+    }
+
+    #[test]
+    fn atomic_concurrency_test() {
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let hotp = AtomicHotp::new(secret, 0, 6, DEFAULT_ALGORITHM);
+        let mut counters: Vec<u64> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..50)
+                            .map(|_| hotp.next_with_counter().0)
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        counters.sort_unstable();
+        counters.dedup();
+        assert_eq!(counters.len(), 400);
+        assert_eq!(hotp.counter(), 400);
+    }
+
+    /// A small xorshift generator, so the adversarial inputs are reproducible.
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn adversarial_make_test() {
+        let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for digits in (0..=64).chain(Some(u32::MAX)) {
+            let code = hotp.make(MakeOption::Full {
+                counter: xorshift(&mut state),
+                digits,
+                algorithm: &ShaTypes::Sha2_512,
+            });
+            assert_eq!(code.len(), digits.clamp(1, MAX_DIGITS) as usize);
+            let code = hotp.make_radix(xorshift(&mut state), digits, 2, &ShaTypes::Sha1);
+            assert_eq!(code.len(), digits.clamp(1, 32) as usize);
+        }
+        let code = hotp.make(MakeOption::Counter(u64::MAX));
+        assert_eq!(code.len(), 6);
+        let empty = Hotp::new(Vec::new());
+        assert_eq!(empty.make(MakeOption::Default).len(), 6);
+    }
+
+    #[test]
+    fn adversarial_check_test() {
+        let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..256 {
+            let len = (xorshift(&mut state) % 24) as usize;
+            let otp: String = (0..len)
+                .map(|_| char::from_u32((xorshift(&mut state) % 0x3000) as u32).unwrap_or('0'))
+                .collect();
+            let counter = match xorshift(&mut state) % 3 {
+                0 => 0,
+                1 => u64::MAX,
+                _ => xorshift(&mut state),
+            };
+            hotp.check(
+                &otp,
+                CheckOption::Full {
+                    counter,
+                    breadth: xorshift(&mut state) % 4,
+                    algorithm: DEFAULT_ALGORITHM,
+                },
+            );
+        }
+        assert!(!hotp.check("", CheckOption::Breadth(3)));
+        assert!(!hotp.check("1234567890123", CheckOption::Counter(u64::MAX)));
+    }
+
+    #[test]
+    fn check_breadth_bounds_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        // A breadth wider than the counter clamps the window at 0.
+        assert!(hotp.check("755224", CheckOption::Breadth(5)));
+        assert!(hotp.check("254676", CheckOption::Breadth(5)));
+        assert!(!hotp.check("287922", CheckOption::Breadth(5)));
+        let full = |counter, breadth| CheckOption::Full {
+            counter,
+            breadth,
+            algorithm: DEFAULT_ALGORITHM,
+        };
+        // The window starts at 0, so the match at counter 1 is found right away.
+        assert!(hotp.check("287082", full(0, u64::MAX)));
+        let last = hotp.make(MakeOption::Counter(u64::MAX));
+        // A breadth past the last counter clamps the window at `u64::MAX`.
+        assert!(hotp.check(&last, full(u64::MAX - 1, 5)));
+        assert!(hotp.check(&last, full(u64::MAX, 5)));
+        let encoder = |value: u32| format_code(value, 6);
+        assert!(hotp.check_with("755224", encoder, CheckOption::Breadth(5)));
+        assert!(hotp.check_with(&last, encoder, full(u64::MAX, 5)));
+    }
+
+    /// Taken from [RFC 4226](https://datatracker.ietf.org/doc/html/rfc4226#section-5.4)
+    #[test]
+    fn const_truncation_test() {
+        const DIGEST: [u8; 20] = [
+            0x1f, 0x86, 0x98, 0x69, 0x0e, 0x02, 0xca, 0x16, 0x61, 0x85, 0x50, 0xef, 0x7f, 0x19,
+            0xda, 0x8e, 0x94, 0x5b, 0x55, 0x5a,
+        ];
+        const VALUE: u32 = dynamic_truncation(&DIGEST);
+        const _: () = assert!(VALUE == 0x50ef_7f19);
+        const _: () = assert!(code_value(VALUE, 6) == 872_921);
+        assert_eq!(VALUE, 1_357_872_921);
+        assert_eq!(code_value(VALUE, 10), VALUE);
+        assert_eq!(dynamic_truncation(&[]), 0);
+        assert_eq!(dynamic_truncation(&[0x0f; 8]), 0);
+    }
+
+    #[test]
+    fn check_with_hint_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let at_hint = hotp.make(MakeOption::Counter(100));
+        assert_eq!(
+            hotp.check_with_hint(&at_hint, 100, 0, 0, DEFAULT_ALGORITHM),
+            Some(100)
+        );
+        assert_eq!(
+            hotp.check_with_hint(&at_hint, 99, 1, 50, DEFAULT_ALGORITHM),
+            Some(100)
+        );
+        let far = hotp.make(MakeOption::Counter(140));
+        assert_eq!(
+            hotp.check_with_hint(&far, 100, 2, 2, DEFAULT_ALGORITHM),
+            None
+        );
+        assert_eq!(
+            hotp.check_with_hint(&far, 100, 2, 50, DEFAULT_ALGORITHM),
+            Some(140)
+        );
+        // A narrow window wider than the wide one still matches.
+        assert_eq!(
+            hotp.check_with_hint(&far, 100, 50, 0, DEFAULT_ALGORITHM),
+            Some(140)
+        );
+        let first = hotp.make(MakeOption::Counter(0));
+        assert_eq!(
+            hotp.check_with_hint(&first, 1, 0, 5, DEFAULT_ALGORITHM),
+            Some(0)
+        );
+        assert_eq!(hotp.check_with_hint("", 0, 0, 5, DEFAULT_ALGORITHM), None);
+    }
+
+    #[test]
+    fn check_tagged_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        assert!(hotp.check_tagged("3:969429", &ShaTypes::Sha1));
+        assert!(hotp.check_tagged("0:755 224\n", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged("2:969429", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged("x:123456", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged("-3:969429", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged("969429", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged("3:", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged(":969429", &ShaTypes::Sha1));
+        assert!(!hotp.check_tagged("3:969429:1", &ShaTypes::Sha1));
+    }
+
+    #[test]
+    fn check_with_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let hex = |value: u32| format!("{:08x}", value);
+        // 0x41397eea is the truncated value behind the RFC 4226 counter 1 code 287082.
+        assert!(hotp.check_with("41397eea", hex, CheckOption::Counter(1)));
+        assert!(hotp.check_with(
+            "41397eea",
+            hex,
+            CheckOption::Full {
+                counter: 3,
+                breadth: 2,
+                algorithm: &ShaTypes::Sha1,
+            }
+        ));
+        assert!(!hotp.check_with("41397eea", hex, CheckOption::Counter(2)));
+        assert!(!hotp.check_with("41397EEA", hex, CheckOption::Counter(1)));
+        let decimal = |value: u32| format!("{:06}", value % 1_000_000);
+        assert!(hotp.check_with("287082", decimal, CheckOption::Counter(1)));
+    }
+
+    #[test]
+    fn counter_check_digit_test() {
+        // Luhn check digits of well-known payloads.
+        assert_eq!(counter_check_digit(7_992_739_871), '3');
+        assert_eq!(counter_check_digit(0), '0');
+        assert_eq!(counter_check_digit(1), '8');
+        assert!(counter_check_digit(u64::MAX).is_ascii_digit());
+        for counter in 0..1_000 {
+            assert_ne!(
+                counter_check_digit(counter),
+                counter_check_digit(counter + 1)
+            );
+        }
+    }
+
+    #[test]
+    fn counter_check_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let first = hotp.make_with_counter_check(1, 6, &ShaTypes::Sha1);
+        let second = hotp.make_with_counter_check(2, 6, &ShaTypes::Sha1);
+        assert_eq!(first, "2870828");
+        assert_eq!(&second[..6], "359152");
+        assert_ne!(first[6..], second[6..]);
+        assert!(hotp.check_with_counter_check(&first, 1, 0, &ShaTypes::Sha1));
+        assert!(hotp.check_with_counter_check("287 082 8", 2, 1, &ShaTypes::Sha1));
+        assert!(!hotp.check_with_counter_check(&first, 2, 0, &ShaTypes::Sha1));
+        for check in (b'0'..=b'9').map(char::from).filter(|&c| c != '8') {
+            let mismatched = format!("287082{}", check);
+            assert!(!hotp.check_with_counter_check(&mismatched, 1, 1, &ShaTypes::Sha1));
+        }
+        assert!(!hotp.check_with_counter_check("287082", 1, 0, &ShaTypes::Sha1));
+        assert!(!hotp.check_with_counter_check("8", 1, 0, &ShaTypes::Sha1));
+        assert!(!hotp.check_with_counter_check("", 1, 0, &ShaTypes::Sha1));
+    }
+
+    #[test]
+    fn combined_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let otp = hotp.make_combined(3, 30, 8, &ShaTypes::Sha1, 59);
+        // RFC 4226 counter 3 with 8 digits, then the RFC 6238 vector at T = 59.
+        assert_eq!(otp.len(), 16);
+        let (event, time) = otp.split_at(8);
+        assert!(hotp.check(event, CheckOption::Counter(3)));
+        assert_eq!(time, "94287082");
+        assert!(hotp.check_combined(&otp, 3, 30, 8, &ShaTypes::Sha1, 0, 59));
+        assert!(hotp.check_combined(&otp, 3, 30, 8, &ShaTypes::Sha1, 1, 89));
+        assert!(!hotp.check_combined(&otp, 3, 30, 8, &ShaTypes::Sha1, 0, 89));
+        assert!(!hotp.check_combined(&otp, 4, 30, 8, &ShaTypes::Sha1, 1, 59));
+        assert!(!hotp.check_combined(&otp[..15], 3, 30, 8, &ShaTypes::Sha1, 1, 59));
+        assert!(!hotp.check_combined(&otp, 3, 30, 6, &ShaTypes::Sha1, 1, 59));
+    }
+
+    #[test]
+    fn salt_test() {
+        let secret = "12345678901234567890".as_bytes();
+        let hotp = Hotp::new(secret.to_vec());
+        let salted = Hotp::new(secret.to_vec()).with_salt(b"account-1");
+        let other = Hotp::new(secret.to_vec()).with_salt(b"account-2");
+        let otp = salted.make(MakeOption::Counter(1));
+        assert_ne!(otp, hotp.make(MakeOption::Counter(1)));
+        assert_ne!(otp, other.make(MakeOption::Counter(1)));
+        assert!(salted.check(&otp, CheckOption::Counter(1)));
+        assert!(!hotp.check(&otp, CheckOption::Counter(1)));
+        assert!(!other.check(&otp, CheckOption::Counter(1)));
+        // The salt is length-prefixed, unlike a pepper.
+        assert_ne!(otp, hotp.make_peppered(1, b"account-1", 6, &ShaTypes::Sha1));
+        assert_eq!(
+            salted_message(&[1], b"ab", b"c"),
+            [1, 0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b', 0, 0, 0, 0, 0, 0, 0, 1, b'c']
+        );
+        // Moving bytes between the salt and the pepper changes the code.
+        let ab = Hotp::new(secret.to_vec()).with_salt(b"ab");
+        let a = Hotp::new(secret.to_vec()).with_salt(b"a");
+        assert_ne!(
+            ab.make_peppered(1, b"c", 6, &ShaTypes::Sha1),
+            a.make_peppered(1, b"bc", 6, &ShaTypes::Sha1)
+        );
+        assert_ne!(
+            salted_message(&[1], b"ab", b"c"),
+            salted_message(&[1], b"a", b"bc")
+        );
+        // An empty salt yields the RFC 4226 code.
+        let empty = Hotp::new(secret.to_vec()).with_salt(b"");
+        assert_eq!(empty.make(MakeOption::Counter(1)), "287082");
+        assert!(format!("{:?}", salted).contains("salt"));
+    }
+
+    #[test]
+    fn peppered_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let otp = hotp.make_peppered(1, b"pepper", 6, &ShaTypes::Sha1);
+        assert_eq!(otp, "881114");
+        assert_ne!(otp, hotp.make(MakeOption::Counter(1)));
+        assert!(hotp.check_peppered(&otp, 1, 0, b"pepper", &ShaTypes::Sha1));
+        assert!(hotp.check_peppered("881 114", 3, 2, b"pepper", &ShaTypes::Sha1));
+        assert!(!hotp.check_peppered(&otp, 1, 0, b"salt", &ShaTypes::Sha1));
+        assert!(!hotp.check_peppered(&otp, 1, 0, b"", &ShaTypes::Sha1));
+        assert!(!hotp.check(&otp, CheckOption::Counter(1)));
+        for counter in 0..10 {
+            assert_eq!(
+                hotp.make_peppered(counter, b"", 6, &ShaTypes::Sha1),
+                hotp.make(MakeOption::Counter(counter))
+            );
+        }
+    }
+
+    #[test]
+    fn counter_len_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let short = Interop {
+            counter_len: 4,
+            ..Interop::default()
+        };
+        // HMAC-SHA-1 over the 4-byte big-endian counter.
+        assert_eq!(hotp.make_interop(0, 6, &ShaTypes::Sha1, &short), "613114");
+        assert_eq!(hotp.make_interop(1, 6, &ShaTypes::Sha1, &short), "675152");
+        assert_eq!(
+            hotp.make_interop(0x1_0000_0001, 6, &ShaTypes::Sha1, &short),
+            "675152"
+        );
+        let long = Interop {
+            counter_len: 10,
+            ..Interop::default()
+        };
+        assert_eq!(hotp.make_interop(1, 6, &ShaTypes::Sha1, &long), "563920");
+        let rfc = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        for (counter, code) in rfc.iter().enumerate() {
+            assert_eq!(
+                hotp.make_interop(counter as u64, 6, &ShaTypes::Sha1, &Interop::default()),
+                *code
+            );
+        }
+        assert_eq!(counter_message_len(0x0102, 1), [0x02]);
+        assert!(counter_message_len(0x0102, 0).is_empty());
+        assert_eq!(
+            counter_message_len(0x0102, 9),
+            [0, 0, 0, 0, 0, 0, 0, 0x01, 0x02]
+        );
+    }
+
+    #[test]
+    fn fixed_offset_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let fixed = Interop {
+            offset: OffsetSource::FixedOffset(0),
+            ..Interop::default()
+        };
+        // The dynamic offset of counter 0 already is 0.
+        let expected = ["755224", "717529", "868666", "023335"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(
+                hotp.make_interop(counter as u64, 6, &ShaTypes::Sha1, &fixed),
+                *code
+            );
+        }
+        assert_eq!(
+            hotp.make_interop(1, 6, &ShaTypes::Sha1, &Interop::default()),
+            "287082"
+        );
+        let digest = [0x12, 0x34, 0x56, 0x78, 0x9a];
+        assert_eq!(
+            truncation(&digest, OffsetSource::FixedOffset(1)),
+            0x3456_789a
+        );
+        assert_eq!(truncation(&digest, OffsetSource::FixedOffset(2)), 0);
+        assert_eq!(
+            truncation(&digest, OffsetSource::FixedOffset(usize::MAX)),
+            0
+        );
+        assert_eq!(truncation(&digest[..3], OffsetSource::FixedOffset(0)), 0);
+    }
+
+    #[test]
+    fn legacy_java_unmasked_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let legacy = Interop {
+            legacy_java_unmasked: true,
+            ..Interop::default()
+        };
+        // The offset byte of counter 0 is 0xcc: the RFC code is 755224.
+        assert_eq!(hotp.make_interop(0, 6, &ShaTypes::Sha1, &legacy), "-728424");
+        assert_eq!(
+            hotp.make_interop(0, 6, &ShaTypes::Sha1, &Interop::default()),
+            "755224"
+        );
+        // The offset byte of counter 2 is 0x5e: both agree.
+        assert_eq!(
+            hotp.make_interop(2, 6, &ShaTypes::Sha1, &legacy),
+            hotp.make(MakeOption::Counter(2))
+        );
+    }
+
+    #[test]
+    fn format_legacy_java_test() {
+        let offset = OffsetSource::FixedOffset(0);
+        assert_eq!(unmasked_truncation(&[0x80, 0, 0, 0], offset), i32::MIN);
+        assert_eq!(unmasked_truncation(&[], OffsetSource::DynamicOffset), 0);
+        assert_eq!(format_legacy_java(-862_728_424, 6), "-728424");
+        assert_eq!(format_legacy_java(-12, 6), "000-12");
+        assert_eq!(format_legacy_java(42, 6), "000042");
+        assert_eq!(format_legacy_java(i32::MIN, 10), "-2147483648");
+        assert_eq!(format_legacy_java(42, u32::MAX), "0000000042");
+    }
+
+    #[test]
+    fn format_code_radix_test() {
+        assert_eq!(format_code_radix(0x50ef_7f19, 6, 16), "ef7f19");
+        assert_eq!(format_code_radix(0x50ef_7f19, 10, 16), "0050ef7f19");
+        assert_eq!(format_code_radix(5, 4, 2), "0101");
+        assert_eq!(format_code_radix(5, 40, 2), format!("{:032b}", 5));
+        assert_eq!(format_code_radix(35, u32::MAX, 36), format!("{:0>32}", "z"));
+        assert_eq!(format_code_radix(35, 1, 36), "z");
+        assert_eq!(format_code_radix(35, 1, 99), "z");
+        for &value in [0, 7, 1_357_872_921, u32::MAX].iter() {
+            for digits in 0..=MAX_DIGITS {
+                assert_eq!(
+                    format_code_radix(value, digits, 10),
+                    format_code(value, digits)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn min_secret_bytes_for_bits_test() {
+        assert_eq!(min_secret_bytes_for_bits(128.0), 16);
+        assert_eq!(min_secret_bytes_for_bits(160.0), 20);
+        assert_eq!(min_secret_bytes_for_bits(161.0), 21);
+        assert_eq!(min_secret_bytes_for_bits(0.5), 1);
+        assert_eq!(min_secret_bytes_for_bits(0.0), 0);
+        assert_eq!(min_secret_bytes_for_bits(-8.0), 0);
+        assert_eq!(min_secret_bytes_for_bits(f64::NAN), 0);
+        assert_eq!(min_secret_bytes_for_bits(f64::INFINITY), usize::MAX);
+    }
+
+    #[test]
+    fn counter_message_one_test() {
+        assert_eq!(counter_message(1), [0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn check_counter_message() {
         let value = 1024_u64;
-        let result = u64_to_8_length_u8_array(value);
+        let result = counter_message(value);
         let expected = [00_u8, 00_u8, 00_u8, 00_u8, 00_u8, 00_u8, 4_u8, 00_u8];
         assert_eq!(result, expected)
     }
 
     #[test]
-    fn check_max_u64_to_8_length_u8_array() {
+    fn check_max_counter_message() {
         let value = u64::MAX;
-        let result = u64_to_8_length_u8_array(value);
+        let result = counter_message(value);
         let expected = [
             255_u8, 255_u8, 255_u8, 255_u8, 255_u8, 255_u8, 255_u8, 255_u8,
         ];
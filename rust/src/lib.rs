@@ -5,14 +5,54 @@ Fast and easy HOTP and TOTP implementation.
 The [OOTP for Rust](https://crates.io/crates/ootp) library is a [Rust](https://www.rust-lang.org/) implementation of the [OOTP](https://github.com/odroe/ootp) library.
 */
 
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
 
+/// Account descriptors for backup and export.
+pub mod account;
+/// Labels of the supported SHA algorithms.
+pub mod algorithm;
+/// Time sources used to compute the TOTP moving factor.
+pub mod clock;
 /// Constants module.
 pub mod constants;
+/// Encodings of shared secrets.
+pub mod encoding;
+/// C-compatible functions to make and check codes from other languages.
+#[cfg(feature = "ffi")]
+pub mod ffi;
 /// HOTP is a HMAC-based one-time password algorithm.
 pub mod hotp;
+/// BIP39 mnemonic representation of shared secrets.
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+/// Derivation of shared secrets from short PINs.
+#[cfg(feature = "pbkdf2")]
+pub mod pin;
+/// HOTP with a precomputed HMAC key, for generating many codes of one secret.
+#[cfg(feature = "prepared")]
+pub mod prepared;
+/// QR codes of provisioning URIs, as SVG documents.
+#[cfg(feature = "qr")]
+pub mod qr;
+/// Shared secrets held encrypted at rest and decrypted on demand.
+#[cfg(feature = "encrypted-secret")]
+pub mod secret;
+/// Shamir secret sharing reconstruction of shared secrets.
+#[cfg(feature = "sss")]
+pub mod sss;
 /// TOTP is a Time-based one-time password algorithm, with a time value as moving factor.
 pub mod totp;
+/// Parsing and emission of `otpauth://` provisioning URIs.
+pub mod uri;
+/// Stateful verifiers for replay protection, rate limiting and monitoring.
+pub mod validator;
+/// Best-effort overwriting of secrets with zeros.
+#[cfg(any(feature = "encrypted-secret", feature = "sss"))]
+mod wipe;
+// Re-export bip39 to handle mnemonic errors.
+#[cfg(feature = "mnemonic")]
+pub use bip39;
 // Re-export hmacsha to handle different SHA algorithms.
 pub use hmacsha;
 
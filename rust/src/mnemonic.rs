@@ -0,0 +1,162 @@
+use crate::hotp::Hotp;
+use bip39::{Error, Mnemonic};
+
+/// The most secret bytes encoded by one phrase of the chunked encoding, as 24 words.
+const CHUNK_LEN: usize = 32;
+
+impl Hotp {
+    /**
+    Encodes the secret as English BIP39 mnemonic words, so that it can be written down.
+
+    BIP39 only encodes 16 to 32 bytes in steps of 4, which covers the usual 20-byte
+    (SHA1) and 32-byte (SHA256) secrets: those are written as a single standard phrase.
+    Secrets of any other length, such as 10-byte or 64-byte (SHA512) ones, are written as
+    their length in bytes and a colon, followed by phrases of 32-byte chunks separated by
+    " / ", the last chunk padded with zeros to a length BIP39 encodes.
+
+    # Example
+
+    ```
+    use ootp::hotp::Hotp;
+
+    let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+    let words = hotp.to_mnemonic().unwrap();
+    assert_eq!(words.split(' ').count(), 15);
+    assert_eq!(Hotp::from_mnemonic(&words).unwrap().secret(), hotp.secret());
+
+    let hotp = Hotp::new(vec![0xff; 64]);
+    let words = hotp.to_mnemonic().unwrap();
+    assert!(words.starts_with("64: zoo "));
+    assert_eq!(Hotp::from_mnemonic(&words).unwrap().secret(), hotp.secret());
+    ```
+    */
+    pub fn to_mnemonic(&self) -> Result<String, Error> {
+        let secret = self.secret();
+        if is_phrase_len(secret.len()) {
+            return Mnemonic::from_entropy(&secret).map(|mnemonic| mnemonic.to_string());
+        }
+        let phrases = secret
+            .chunks(CHUNK_LEN)
+            .map(|chunk| {
+                let mut entropy = chunk.to_vec();
+                entropy.resize(padded_len(chunk.len()), 0);
+                Mnemonic::from_entropy(&entropy).map(|mnemonic| mnemonic.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("{}: {}", secret.len(), phrases.join(" / ")))
+    }
+
+    /**
+    HOTP instance constructor from English BIP39 mnemonic words, as written by `to_mnemonic`.
+
+    A length prefix that is not a number is reported as `bip39::Error::UnknownWord(0)`,
+    phrases that do not encode that many bytes as `bip39::Error::BadEntropyBitCount`.
+    */
+    pub fn from_mnemonic(words: &str) -> Result<Hotp, Error> {
+        let (len, phrases) = match words.split_once(':') {
+            Some(split) => split,
+            None => return Mnemonic::parse(words).map(|mnemonic| Hotp::new(mnemonic.to_entropy())),
+        };
+        let len: usize = len.trim().parse().map_err(|_| Error::UnknownWord(0))?;
+        let mut secret = Vec::with_capacity(len);
+        for phrase in phrases
+            .split('/')
+            .filter(|phrase| !phrase.trim().is_empty())
+        {
+            let entropy = Mnemonic::parse(phrase)?.to_entropy();
+            let chunk_len = (len - secret.len()).min(CHUNK_LEN);
+            if chunk_len == 0
+                || entropy.len() != padded_len(chunk_len)
+                || entropy[chunk_len..].iter().any(|&byte| byte != 0)
+            {
+                return Err(Error::BadEntropyBitCount(entropy.len() * 8));
+            }
+            secret.extend_from_slice(&entropy[..chunk_len]);
+        }
+        if secret.len() != len {
+            return Err(Error::BadEntropyBitCount(secret.len() * 8));
+        }
+        Ok(Hotp::new(secret))
+    }
+}
+
+/// Whether BIP39 encodes `len` bytes as a single phrase.
+fn is_phrase_len(len: usize) -> bool {
+    (16..=CHUNK_LEN).contains(&len) && len.is_multiple_of(4)
+}
+
+/// Returns the length a chunk of `len` bytes is padded to, the shortest BIP39 encodes.
+fn padded_len(len: usize) -> usize {
+    len.max(16).next_multiple_of(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hotp::{Hotp, MakeOption};
+    use bip39::Error;
+
+    #[test]
+    fn mnemonic_round_trip_test() {
+        for len in [0, 1, 10, 16, 20, 24, 28, 32, 33, 63, 64, 100].iter() {
+            let secret: Vec<u8> = (0..*len).map(|i| (i * 37 + 11) as u8).collect();
+            let hotp = Hotp::new(secret.clone());
+            let words = hotp.to_mnemonic().unwrap();
+            let restored = Hotp::from_mnemonic(&words).unwrap();
+            assert_eq!(restored.secret(), secret);
+            for counter in 0..10 {
+                assert_eq!(
+                    restored.make(MakeOption::Counter(counter)),
+                    hotp.make(MakeOption::Counter(counter))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mnemonic_rfc_secret_test() {
+        let hotp = Hotp::from_mnemonic(
+            &Hotp::new("12345678901234567890".as_bytes().to_vec())
+                .to_mnemonic()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(hotp.make(MakeOption::Counter(1)), "287082");
+    }
+
+    #[test]
+    fn mnemonic_errors_test() {
+        let words = Hotp::new(vec![0; 16]).to_mnemonic().unwrap();
+        assert_eq!(
+            words,
+            "abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon about"
+        );
+        assert_eq!(
+            Hotp::from_mnemonic(&words.replace("about", "abandon")).err(),
+            Some(Error::InvalidChecksum)
+        );
+        assert!(Hotp::from_mnemonic("not a mnemonic").is_err());
+        // A 10-byte secret is padded to the 16 bytes of the same phrase.
+        assert_eq!(
+            Hotp::new(vec![0; 10]).to_mnemonic(),
+            Ok(format!("10: {}", words))
+        );
+        assert_eq!(
+            Hotp::from_mnemonic(&format!("ten: {}", words)).err(),
+            Some(Error::UnknownWord(0))
+        );
+        assert_eq!(
+            Hotp::from_mnemonic(&format!("20: {}", words)).err(),
+            Some(Error::BadEntropyBitCount(128))
+        );
+        assert_eq!(
+            Hotp::from_mnemonic(&format!("10: {} / {}", words, words)).err(),
+            Some(Error::BadEntropyBitCount(128))
+        );
+        let padding = Hotp::new(vec![1; 16]).to_mnemonic().unwrap();
+        assert_eq!(
+            Hotp::from_mnemonic(&format!("10: {}", padding)).err(),
+            Some(Error::BadEntropyBitCount(128))
+        );
+    }
+}
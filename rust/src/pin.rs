@@ -0,0 +1,69 @@
+use crate::hotp::Hotp;
+use sha2::Sha256;
+
+/// The length, in bytes, of the secrets derived from PINs, the RFC 4226 recommended 160 bits.
+const DERIVED_SECRET_LENGTH: usize = 20;
+
+impl Hotp {
+    /**
+    HOTP instance constructor from a short `pin`, stretched into a 20-byte secret with
+    PBKDF2-HMAC-SHA256 over `salt` and `iterations` rounds.
+
+    **Security caveat:** a PIN has very little entropy. Anyone who gets hold of the salt,
+    the iteration count and a single code can brute-force the PIN offline, however many
+    iterations are used. Prefer a random secret whenever one can be stored, and use a
+    unique random salt per account otherwise.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+
+    let hotp = Hotp::from_pin("1234", b"salt", 1000);
+    assert_eq!(hotp.make(MakeOption::Counter(0)), "749334");
+    ```
+    */
+    pub fn from_pin(pin: &str, salt: &[u8], iterations: u32) -> Hotp {
+        let mut secret = vec![0; DERIVED_SECRET_LENGTH];
+        pbkdf2::pbkdf2_hmac::<Sha256>(pin.as_bytes(), salt, iterations, &mut secret);
+        Hotp::new(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hotp::{Hotp, MakeOption};
+
+    #[test]
+    fn from_pin_test() {
+        let hotp = Hotp::from_pin("1234", b"salt", 1000);
+        assert_eq!(
+            hotp.secret(),
+            [
+                0xfe, 0xf2, 0x92, 0xd2, 0x31, 0xf6, 0xe6, 0xf5, 0xe7, 0x87, 0xb2, 0x2d, 0xb1, 0x28,
+                0xb0, 0xe5, 0x0d, 0x2e, 0x75, 0xd0,
+            ]
+        );
+        assert_eq!(hotp.make(MakeOption::Counter(0)), "749334");
+        assert_eq!(
+            Hotp::from_pin("1234", b"salt", 1000).secret(),
+            hotp.secret()
+        );
+    }
+
+    #[test]
+    fn from_pin_salt_test() {
+        let salted = Hotp::from_pin("1234", b"pepper", 1000);
+        assert_eq!(salted.make(MakeOption::Counter(0)), "566362");
+        let hotp = Hotp::from_pin("1234", b"salt", 1000);
+        assert_ne!(salted.secret(), hotp.secret());
+        assert_ne!(
+            Hotp::from_pin("1234", b"salt", 1001).secret(),
+            hotp.secret()
+        );
+        assert_ne!(
+            Hotp::from_pin("1235", b"salt", 1000).secret(),
+            hotp.secret()
+        );
+    }
+}
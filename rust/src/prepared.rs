@@ -0,0 +1,159 @@
+use crate::algorithm::OtpAlgorithmId;
+use crate::constants::DEFAULT_DIGITS;
+use crate::hotp::{counter_message, dynamic_truncation, format_code, salted_message, Hotp};
+use hmac::{Hmac, Mac};
+use hmacsha::ShaTypes;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use sha3::{Sha3_256, Sha3_512};
+use std::fmt;
+
+/// An HMAC keyed once, i.e. with its inner and outer padded key already absorbed.
+#[derive(Clone)]
+enum KeyedMac {
+    Sha1(Hmac<Sha1>),
+    Sha256(Hmac<Sha256>),
+    Sha512(Hmac<Sha512>),
+    Sha3_256(Hmac<Sha3_256>),
+    Sha3_512(Hmac<Sha3_512>),
+}
+
+impl KeyedMac {
+    fn new(secret: &[u8], algorithm: OtpAlgorithmId) -> Self {
+        const ANY_KEY_LENGTH: &str = "HMAC can take key of any size";
+        match algorithm {
+            OtpAlgorithmId::Sha1 => {
+                KeyedMac::Sha1(Hmac::new_from_slice(secret).expect(ANY_KEY_LENGTH))
+            }
+            OtpAlgorithmId::Sha256 => {
+                KeyedMac::Sha256(Hmac::new_from_slice(secret).expect(ANY_KEY_LENGTH))
+            }
+            OtpAlgorithmId::Sha512 => {
+                KeyedMac::Sha512(Hmac::new_from_slice(secret).expect(ANY_KEY_LENGTH))
+            }
+            OtpAlgorithmId::Sha3_256 => {
+                KeyedMac::Sha3_256(Hmac::new_from_slice(secret).expect(ANY_KEY_LENGTH))
+            }
+            OtpAlgorithmId::Sha3_512 => {
+                KeyedMac::Sha3_512(Hmac::new_from_slice(secret).expect(ANY_KEY_LENGTH))
+            }
+        }
+    }
+
+    /// Computes the HMAC of `message` from a copy of the keyed state.
+    fn truncate(&self, message: &[u8]) -> u32 {
+        fn digest<M: Mac + Clone>(mac: &M, message: &[u8]) -> u32 {
+            let mut mac = mac.clone();
+            mac.update(message);
+            dynamic_truncation(&mac.finalize().into_bytes())
+        }
+        match self {
+            KeyedMac::Sha1(mac) => digest(mac, message),
+            KeyedMac::Sha256(mac) => digest(mac, message),
+            KeyedMac::Sha512(mac) => digest(mac, message),
+            KeyedMac::Sha3_256(mac) => digest(mac, message),
+            KeyedMac::Sha3_512(mac) => digest(mac, message),
+        }
+    }
+}
+
+/**
+An `Hotp` with its HMAC key padding precomputed, to make many codes of the same secret
+(e.g. for different counters) without re-keying the HMAC every time.
+
+Its `Debug` output omits the key.
+
+# Example
+
+```
+use ootp::hotp::{Hotp, MakeOption};
+use ootp::hmacsha::ShaTypes;
+
+let hotp = Hotp::new("A strong shared secret".as_bytes().to_vec());
+let prepared = hotp.prepared(&ShaTypes::Sha1);
+assert_eq!(prepared.make_counter(42), hotp.make(MakeOption::Counter(42)));
+```
+*/
+#[derive(Clone)]
+pub struct PreparedHotp {
+    mac: KeyedMac,
+    salt: Vec<u8>,
+    pub algorithm: OtpAlgorithmId,
+    pub digits: u32,
+}
+
+impl fmt::Debug for PreparedHotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedHotp")
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PreparedHotp {
+    /// Sets the number of digits of the codes, `DEFAULT_DIGITS` by default.
+    pub fn with_digits(mut self, digits: u32) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    /// Returns the code of `counter`, like `Hotp::make` with the same algorithm and digits.
+    pub fn make_counter(&self, counter: u64) -> String {
+        let message = salted_message(&counter_message(counter), &self.salt, b"");
+        format_code(self.mac.truncate(&message), self.digits)
+    }
+}
+
+impl Hotp {
+    /// Returns a `PreparedHotp` of this secret, keyed once for `algorithm`.
+    pub fn prepared(&self, algorithm: &ShaTypes) -> PreparedHotp {
+        let algorithm = OtpAlgorithmId::from_sha_type(algorithm);
+        PreparedHotp {
+            mac: KeyedMac::new(&self.secret(), algorithm),
+            salt: self.salt().to_vec(),
+            algorithm,
+            digits: DEFAULT_DIGITS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::supported_algorithms;
+    use crate::hotp::{Hotp, MakeOption};
+
+    #[test]
+    fn prepared_parity_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        for &(_, algorithm) in supported_algorithms() {
+            let prepared = hotp.prepared(algorithm).with_digits(8);
+            for counter in 0..=9 {
+                assert_eq!(
+                    prepared.make_counter(counter),
+                    hotp.make(MakeOption::Full {
+                        counter,
+                        digits: 8,
+                        algorithm,
+                    })
+                );
+            }
+        }
+        // RFC 4226 appendix D vectors.
+        let prepared = hotp.prepared(&hmacsha::ShaTypes::Sha1);
+        assert_eq!(prepared.make_counter(0), "755224");
+        assert_eq!(prepared.make_counter(9), "520489");
+        let salted = Hotp::new("12345678901234567890".as_bytes().to_vec()).with_salt(b"salt");
+        assert_eq!(
+            salted.prepared(&hmacsha::ShaTypes::Sha1).make_counter(1),
+            salted.make(MakeOption::Counter(1))
+        );
+    }
+
+    #[test]
+    fn prepared_debug_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let debug = format!("{:?}", hotp.prepared(&hmacsha::ShaTypes::Sha1));
+        assert_eq!(debug, "PreparedHotp { algorithm: Sha1, digits: 6, .. }");
+    }
+}
@@ -0,0 +1,690 @@
+use crate::totp::Totp;
+use std::fmt::Write;
+
+/// The error correction codewords per block at level M, by version.
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+
+/// The error correction blocks at level M, by version.
+const ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23,
+    25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+
+/// The light modules around the symbol, the minimum required by ISO/IEC 18004.
+const QUIET_ZONE: usize = 4;
+
+/**
+A QR code symbol encoding bytes in byte mode at error correction level M, the level
+authenticator apps scan provisioning URIs at, in the smallest of the 40 versions that fits.
+
+# Example
+
+```
+use ootp::qr::QrCode;
+
+let qr = QrCode::encode(b"otpauth://totp/alice?secret=JBSWY3DPEHPK3PXP").unwrap();
+assert_eq!(qr.size(), 33);
+assert!(qr.module(0, 0));
+```
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `data`, or returns `None` if it is longer than the 2331 bytes of version 40.
+    pub fn encode(data: &[u8]) -> Option<QrCode> {
+        let version = (1..=40).find(|&version| data.len() <= byte_capacity(version))?;
+        let codewords = add_ecc_and_interleave(&data_codewords(data, version), version);
+        let mut qr = QrCode::with_function_patterns(version);
+        qr.draw_codewords(&codewords);
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                let mut masked = qr.clone();
+                masked.apply_mask(mask);
+                masked.draw_format_bits(mask);
+                masked.penalty()
+            })
+            .unwrap_or(0);
+        qr.apply_mask(mask);
+        qr.draw_format_bits(mask);
+        Some(qr)
+    }
+
+    /// Returns the number of modules per side, from 21 to 177.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns a boolean indicating if the module at column `x` and row `y` is dark.
+    /// Modules outside the symbol are light.
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+
+    /// Returns an SVG document of the symbol with a quiet zone of `border` modules,
+    /// one unit per module.
+    pub fn to_svg(&self, border: usize) -> String {
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in (0..self.size).filter(|&x| self.module(x, y)) {
+                if !path.is_empty() {
+                    path.push(' ');
+                }
+                let _ = write!(path, "M{},{}h1v1h-1z", x + border, y + border);
+            }
+        }
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {0} {0}\" \
+             stroke=\"none\" shape-rendering=\"crispEdges\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n\
+             <path d=\"{1}\" fill=\"#000000\"/>\n\
+             </svg>\n",
+            self.size + 2 * border,
+            path
+        )
+    }
+
+    /// Returns a symbol of `version` with only the function patterns drawn, the format
+    /// area reserved.
+    fn with_function_patterns(version: usize) -> QrCode {
+        let size = version * 4 + 17;
+        let mut qr = QrCode {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        for i in 0..size {
+            qr.set_function(6, i, i % 2 == 0);
+            qr.set_function(i, 6, i % 2 == 0);
+        }
+        qr.draw_finder(3, 3);
+        qr.draw_finder(size - 4, 3);
+        qr.draw_finder(3, size - 4);
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // The corners overlapping the finder patterns have no alignment pattern.
+                if ![(0, 0), (0, last), (last, 0)].contains(&(i, j)) {
+                    qr.draw_alignment(x, y);
+                }
+            }
+        }
+        qr.draw_format_bits(0);
+        qr.draw_version_bits(version);
+        qr
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    /// Draws the finder pattern centered on `x`, `y` and its separator.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4isize..=4 {
+            for dx in -4isize..=4 {
+                let (fx, fy) = (x as isize + dx, y as isize + dy);
+                let size = self.size as isize;
+                if (0..size).contains(&fx) && (0..size).contains(&fy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(fx as usize, fy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    /// Draws the alignment pattern centered on `x`, `y`.
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2isize..=2 {
+            for dx in -2isize..=2 {
+                let distance = dx.abs().max(dy.abs());
+                let (ax, ay) = (x as isize + dx, y as isize + dy);
+                self.set_function(ax as usize, ay as usize, distance != 1);
+            }
+        }
+    }
+
+    /// Draws both copies of the format information of `mask` and the dark module.
+    fn draw_format_bits(&mut self, mask: u8) {
+        let bits = format_bits(mask);
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Draws both copies of the version information, present from version 7.
+    fn draw_version_bits(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let bits = version_bits(version);
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let (a, b) = (self.size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Draws `codewords` in the zigzag order of two-module columns, right to left,
+    /// skipping the function modules.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            // The vertical timing pattern shifts the columns on its left.
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                for x in [right, right - 1] {
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.function[y * size + x] && i < codewords.len() * 8 {
+                        self.modules[y * size + x] = (codewords[i / 8] >> (7 - i % 8)) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if !self.function[y * self.size + x] && mask_bit(mask, x, y) {
+                    self.modules[y * self.size + x] ^= true;
+                }
+            }
+        }
+    }
+
+    /// Returns the ISO/IEC 18004 penalty of the symbol, the lower the easier to scan.
+    fn penalty(&self) -> usize {
+        const FINDER_LIKE: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let size = self.size;
+        let rows = (0..size).map(|y| (0..size).map(|x| self.module(x, y)).collect::<Vec<_>>());
+        let columns = (0..size).map(|x| (0..size).map(|y| self.module(x, y)).collect::<Vec<_>>());
+        let mut penalty = 0;
+        for line in rows.chain(columns) {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += run - 2;
+                }
+                run = 1;
+            }
+            penalty += 40
+                * line
+                    .windows(FINDER_LIKE.len())
+                    .filter(|window| {
+                        window.iter().eq(FINDER_LIKE.iter())
+                            || window.iter().eq(FINDER_LIKE.iter().rev())
+                    })
+                    .count();
+        }
+        for y in 1..size {
+            for x in 1..size {
+                let dark = self.module(x, y);
+                if [(x - 1, y), (x, y - 1), (x - 1, y - 1)]
+                    .iter()
+                    .all(|&(x, y)| self.module(x, y) == dark)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        let deviation = (dark * 20).abs_diff(total * 10).div_ceil(total);
+        penalty + deviation.saturating_sub(1) * 10
+    }
+}
+
+impl Totp<'_> {
+    /**
+    Returns an SVG document of the QR code of the `to_uri` provisioning URI, with the
+    quiet zone included, e.g. to embed as a `data:image/svg+xml` image.
+    Returns `None` if the URI does not fit in a QR code, i.e. is longer than 2331 bytes.
+
+    # Example
+
+    ```
+    use ootp::totp::{CreateOption, Totp};
+
+    let totp = Totp::secret("A strong shared secret".as_bytes().to_vec(), CreateOption::Default);
+    let svg = totp.provisioning_qr_svg("ACME Co", "alice@example.com").unwrap();
+    assert!(svg.contains("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    ```
+    */
+    pub fn provisioning_qr_svg(&self, issuer: &str, account: &str) -> Option<String> {
+        QrCode::encode(self.to_uri(issuer, account).as_bytes()).map(|qr| qr.to_svg(QUIET_ZONE))
+    }
+}
+
+/// Returns the number of data bits, excluding error correction, of `version`.
+fn raw_data_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+fn data_codeword_count(version: usize) -> usize {
+    raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[version] * ERROR_CORRECTION_BLOCKS[version]
+}
+
+/// Returns the width of the byte mode character count of `version`.
+const fn count_bits(version: usize) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Returns the number of bytes `version` holds in byte mode.
+fn byte_capacity(version: usize) -> usize {
+    (data_codeword_count(version) * 8 - 4 - count_bits(version)) / 8
+}
+
+/// Returns the byte mode segment of `data`, terminated and padded to the capacity of `version`.
+fn data_codewords(data: &[u8], version: usize) -> Vec<u8> {
+    let capacity = data_codeword_count(version) * 8;
+    let mut bits = Vec::with_capacity(capacity);
+    let mut push = |value: usize, len: usize| {
+        bits.extend((0..len).rev().map(|i| (value >> i) & 1 != 0));
+    };
+    push(0b0100, 4);
+    push(data.len(), count_bits(version));
+    for &byte in data {
+        push(usize::from(byte), 8);
+    }
+    let terminator = (capacity - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    bits.extend(std::iter::repeat_n(false, (8 - bits.len() % 8) % 8));
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)))
+        .collect();
+    let padding = capacity / 8 - codewords.len();
+    codewords.extend([0xec, 0x11].iter().cycle().take(padding));
+    codewords
+}
+
+/// Splits `data` into the error correction blocks of `version`, appends their error
+/// correction codewords and interleaves the blocks.
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let block_count = ERROR_CORRECTION_BLOCKS[version];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = block_count - raw_codewords % block_count;
+    let short_len = raw_codewords / block_count;
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks = Vec::with_capacity(block_count);
+    let mut offset = 0;
+    for i in 0..block_count {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let mut block = data[offset..offset + len].to_vec();
+        offset += len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        // Short blocks get a placeholder, skipped when interleaving.
+        if i < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        blocks.push(block);
+    }
+    let mut codewords = Vec::with_capacity(raw_codewords);
+    for i in 0..=short_len {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                codewords.push(block[i]);
+            }
+        }
+    }
+    codewords
+}
+
+/// Returns the coefficients, highest degree first and leading one omitted, of the
+/// Reed-Solomon generator polynomial of `degree`.
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0; degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_multiply(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    divisor
+}
+
+/// Returns the Reed-Solomon error correction codewords of `data`.
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (value, &coefficient) in remainder.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(coefficient, factor);
+        }
+    }
+    remainder
+}
+
+/// Multiplies in GF(2^8) modulo the QR code polynomial `x^8 + x^4 + x^3 + x^2 + 1`.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut product: u16 = 0;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11d);
+        product ^= u16::from((y >> i) & 1) * u16::from(x);
+    }
+    product as u8
+}
+
+/// Returns the centers of the alignment patterns along each axis of `version`.
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = (version * 8 + count * 3 + 5) / (count * 4 - 4) * 2;
+    let last = version * 4 + 10;
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| last - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// Returns the 15-bit format information of level M and `mask`, BCH protected and masked.
+fn format_bits(mask: u8) -> u32 {
+    let data = u32::from(mask);
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+    }
+    (data << 10 | remainder) ^ 0x5412
+}
+
+/// Returns the 18-bit, BCH protected, version information of `version`.
+fn version_bits(version: usize) -> u32 {
+    let version = version as u32;
+    let mut remainder = version;
+    for _ in 0..12 {
+        remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+    }
+    version << 12 | remainder
+}
+
+/// Returns a boolean indicating if data mask pattern `mask` inverts the module at `x`, `y`.
+fn mask_bit(mask: u8, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_ecc_and_interleave, alignment_positions, byte_capacity, count_bits, format_bits,
+        mask_bit, reed_solomon_divisor, reed_solomon_remainder, version_bits, QrCode,
+        ECC_CODEWORDS_PER_BLOCK, ERROR_CORRECTION_BLOCKS, QUIET_ZONE,
+    };
+    use crate::totp::{CreateOption, Totp};
+    use hmacsha::ShaTypes;
+
+    /// Reads back the byte mode data of a level M symbol of `size` modules per side.
+    fn decode(size: usize, dark: impl Fn(usize, usize) -> bool) -> Vec<u8> {
+        let version = (size - 17) / 4;
+        let format_positions = (0..6)
+            .map(|i| (8, i))
+            .chain(vec![(8, 7), (8, 8), (7, 8)])
+            .chain((9..15).map(|i| (14 - i, 8)));
+        let bits = format_positions
+            .enumerate()
+            .fold(0, |bits, (i, (x, y))| bits | u32::from(dark(x, y)) << i);
+        let format = bits ^ 0x5412;
+        assert_eq!(format >> 13, 0, "not level M");
+        let mask = (format >> 10) as u8 & 7;
+        assert_eq!(format_bits(mask), bits);
+
+        let function = QrCode::with_function_patterns(version).function;
+        let mut raw = Vec::new();
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for x in [right, right - 1] {
+                    let y = if (right + 1) & 2 == 0 {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !function[y * size + x] {
+                        raw.push(dark(x, y) ^ mask_bit(mask, x, y));
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+        let codewords: Vec<u8> = raw
+            .chunks_exact(8)
+            .map(|bits| bits.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)))
+            .collect();
+
+        let block_count = ERROR_CORRECTION_BLOCKS[version];
+        let data_len = codewords.len() / block_count - ECC_CODEWORDS_PER_BLOCK[version];
+        let short_blocks = block_count - codewords.len() % block_count;
+        let mut blocks = vec![Vec::new(); block_count];
+        let mut codewords = codewords.into_iter();
+        for i in 0..=data_len {
+            for (j, block) in blocks.iter_mut().enumerate() {
+                if i < data_len || j >= short_blocks {
+                    block.push(codewords.next().unwrap());
+                }
+            }
+        }
+        let data: Vec<bool> = blocks
+            .concat()
+            .iter()
+            .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 != 0))
+            .collect();
+        let read = |offset: usize, len: usize| {
+            data[offset..offset + len]
+                .iter()
+                .fold(0, |value, &bit| value << 1 | usize::from(bit))
+        };
+        assert_eq!(read(0, 4), 0b0100, "not byte mode");
+        let len = read(4, count_bits(version));
+        let start = 4 + count_bits(version);
+        (0..len).map(|i| read(start + i * 8, 8) as u8).collect()
+    }
+
+    /// Returns the modules per side and the dark modules drawn by `QrCode::to_svg`.
+    fn svg_modules(svg: &str) -> (usize, Vec<(usize, usize)>) {
+        let view_box = svg.split("viewBox=\"0 0 ").nth(1).unwrap();
+        let dimension: usize = view_box.split(' ').next().unwrap().parse().unwrap();
+        let path = svg.split("<path d=\"").nth(1).unwrap();
+        let path = &path[..path.find('"').unwrap()];
+        let modules = path
+            .split(' ')
+            .map(|module| {
+                let module = module.strip_prefix('M').unwrap();
+                let module = module.strip_suffix("h1v1h-1z").unwrap();
+                let (x, y) = module.split_at(module.find(',').unwrap());
+                let x: usize = x.parse().unwrap();
+                let y: usize = y[1..].parse().unwrap();
+                (x - QUIET_ZONE, y - QUIET_ZONE)
+            })
+            .collect();
+        (dimension - 2 * QUIET_ZONE, modules)
+    }
+
+    #[test]
+    fn reed_solomon_test() {
+        // The version 1-M data codewords of "HELLO WORLD" in alphanumeric mode.
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        let ecc = [196, 35, 39, 119, 235, 215, 231, 226, 93, 23];
+        assert_eq!(
+            reed_solomon_remainder(&data, &reed_solomon_divisor(10)),
+            ecc
+        );
+        assert_eq!(
+            add_ecc_and_interleave(&data, 1),
+            [&data[..], &ecc[..]].concat()
+        );
+    }
+
+    #[test]
+    fn format_and_version_bits_test() {
+        let format = [
+            0b101010000010010,
+            0b101000100100101,
+            0b101111001111100,
+            0b101101101001011,
+            0b100010111111001,
+            0b100000011001110,
+            0b100111110010111,
+            0b100101010100000,
+        ];
+        for (mask, &bits) in format.iter().enumerate() {
+            assert_eq!(format_bits(mask as u8), bits, "mask {}", mask);
+        }
+        assert_eq!(version_bits(7), 0b000111110010010100);
+        assert_eq!(version_bits(40), 0b101000110001101001);
+    }
+
+    #[test]
+    fn capacity_test() {
+        let capacities: Vec<usize> = (1..=10).map(byte_capacity).collect();
+        assert_eq!(capacities, [14, 26, 42, 62, 84, 106, 122, 152, 180, 213]);
+        assert_eq!(byte_capacity(40), 2331);
+        assert!(alignment_positions(1).is_empty());
+        assert_eq!(alignment_positions(2), [6, 18]);
+        assert_eq!(alignment_positions(7), [6, 22, 38]);
+        assert_eq!(alignment_positions(32), [6, 34, 60, 86, 112, 138]);
+        assert_eq!(alignment_positions(40), [6, 30, 58, 86, 114, 142, 170]);
+        assert_eq!(QrCode::encode(&[0; 14]).unwrap().size(), 21);
+        assert_eq!(QrCode::encode(&[0; 15]).unwrap().size(), 25);
+        assert_eq!(QrCode::encode(&[0; 2331]).unwrap().size(), 177);
+        assert_eq!(QrCode::encode(&[0; 2332]), None);
+    }
+
+    #[test]
+    fn encode_decode_test() {
+        for &len in [0, 1, 14, 15, 100, 300, 1000, 2331].iter() {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + len) as u8).collect();
+            let qr = QrCode::encode(&data).unwrap();
+            // The finder patterns, checked at their centers and separators.
+            let size = qr.size();
+            for &(x, y) in [(3, 3), (size - 4, 3), (3, size - 4)].iter() {
+                assert!(qr.module(x, y) && !qr.module(x + 2, y) && !qr.module(x, y + 4));
+            }
+            assert_eq!(decode(size, |x, y| qr.module(x, y)), data, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn provisioning_qr_svg_test() {
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Full {
+                digits: 8,
+                period: 60,
+                algorithm: &ShaTypes::Sha2_256,
+            },
+        )
+        .with_salt(b"account-1");
+        let svg = totp
+            .provisioning_qr_svg("ACME Co", "alice@example.com")
+            .unwrap();
+
+        assert!(svg.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg "));
+        assert!(svg.contains(" xmlns=\"http://www.w3.org/2000/svg\" "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches('<').count(), svg.matches('>').count());
+        assert_eq!(svg.matches("<svg").count(), 1);
+        assert_eq!(svg.matches("/>").count(), 2);
+
+        let (size, modules) = svg_modules(&svg);
+        let uri = decode(size, |x, y| modules.contains(&(x, y)));
+        let uri = String::from_utf8(uri).unwrap();
+        assert_eq!(uri, totp.to_uri("ACME Co", "alice@example.com"));
+        let parsed = Totp::from_uri(&uri).unwrap();
+        assert_eq!(parsed.hotp.secret(), totp.hotp.secret());
+        assert_eq!(parsed.hotp.salt(), b"account-1");
+        assert_eq!((parsed.digits, parsed.period), (8, 60));
+        assert!(matches!(parsed.algorithm, ShaTypes::Sha2_256));
+        assert_eq!(
+            parsed.make_time(1_111_111_111),
+            totp.make_time(1_111_111_111)
+        );
+
+        let long = "a".repeat(2400);
+        assert_eq!(totp.provisioning_qr_svg(&long, "alice"), None);
+    }
+}
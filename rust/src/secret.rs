@@ -0,0 +1,230 @@
+use crate::hotp::Hotp;
+use crate::totp::Totp;
+use crate::wipe::wipe;
+
+/// A source of the shared secret, lending it only for the duration of a call.
+pub trait SecretProvider {
+    /// The error of a secret that cannot be provided.
+    type Error;
+
+    /// Calls `f` with the secret, which must not be kept beyond the call.
+    fn with_secret<R>(&self, f: impl FnOnce(&[u8]) -> R) -> Result<R, Self::Error>;
+}
+
+/// Decrypts the ciphertext of an `EncryptedSecret`, e.g. with AES-GCM and a key held by a KMS.
+pub trait SecretCipher {
+    /// The error of a ciphertext that cannot be decrypted.
+    type Error;
+
+    /// Appends the plaintext of `ciphertext` to `plaintext`.
+    ///
+    /// `plaintext` has room for `ciphertext.len()` bytes, so a cipher whose plaintext is no
+    /// longer than its ciphertext never leaves an unwiped copy behind by reallocating.
+    fn decrypt(&self, ciphertext: &[u8], plaintext: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
+
+/**
+A `SecretProvider` holding the secret encrypted at rest, decrypting it with `cipher` on each
+call and overwriting the plaintext with zeros afterwards, even if the call panics.
+
+The wiping is best effort: it covers the buffers of this crate, not copies made by the
+cipher or the HMAC implementation, nor memory swapped out by the operating system.
+
+# Example
+
+```
+use ootp::secret::{EncryptedSecret, SecretCipher};
+use ootp::totp::{CreateOption, Totp};
+
+// Stands in for a real cipher, e.g. AES-GCM with a key from a KMS.
+struct Xor(u8);
+
+impl SecretCipher for Xor {
+    type Error = ();
+
+    fn decrypt(&self, ciphertext: &[u8], plaintext: &mut Vec<u8>) -> Result<(), ()> {
+        plaintext.extend(ciphertext.iter().map(|byte| byte ^ self.0));
+        Ok(())
+    }
+}
+
+let ciphertext = b"12345678901234567890".iter().map(|byte| byte ^ 0x5c).collect();
+let secret = EncryptedSecret { ciphertext, cipher: Xor(0x5c) };
+let totp = Totp::secret(Vec::new(), CreateOption::Digits(8));
+let otp = totp.with_secret_from(&secret, |totp| totp.make_time(59)).unwrap();
+assert_eq!(otp, "94287082");
+```
+*/
+#[derive(Clone, Debug)]
+pub struct EncryptedSecret<C> {
+    /// The encrypted secret.
+    pub ciphertext: Vec<u8>,
+    /// The cipher decrypting `ciphertext`.
+    pub cipher: C,
+}
+
+impl<C: SecretCipher> EncryptedSecret<C> {
+    /// Like `with_secret`, decrypting into `plaintext`, which is left zeroed.
+    fn with_secret_in<R>(
+        &self,
+        plaintext: &mut Vec<u8>,
+        f: impl FnOnce(&[u8]) -> R,
+    ) -> Result<R, C::Error> {
+        let plaintext = WipeOnDrop(plaintext);
+        plaintext.0.reserve(self.ciphertext.len());
+        self.cipher.decrypt(&self.ciphertext, plaintext.0)?;
+        Ok(f(plaintext.0))
+    }
+}
+
+impl<C: SecretCipher> SecretProvider for EncryptedSecret<C> {
+    type Error = C::Error;
+
+    fn with_secret<R>(&self, f: impl FnOnce(&[u8]) -> R) -> Result<R, C::Error> {
+        self.with_secret_in(&mut Vec::new(), f)
+    }
+}
+
+impl<'a> Totp<'a> {
+    /**
+    Calls `f` with a copy of this `Totp` keyed by the secret of `provider`, e.g. to `make` or
+    `check` a code with an `EncryptedSecret`. The copy of the secret is overwritten with
+    zeros when `f` returns or panics; the secret of `self`, typically empty, is not used.
+    */
+    pub fn with_secret_from<P: SecretProvider, R>(
+        &self,
+        provider: &P,
+        f: impl FnOnce(&Totp<'a>) -> R,
+    ) -> Result<R, P::Error> {
+        provider.with_secret(|secret| {
+            let keyed = WipeOnDrop(Totp {
+                hotp: Hotp::new(secret.to_vec()).with_salt(self.hotp.salt()),
+                ..*self
+            });
+            f(&keyed.0)
+        })
+    }
+}
+
+/// Values holding a secret that can be overwritten with zeros.
+trait Wipe {
+    fn wipe(&mut self);
+}
+
+impl Wipe for &mut Vec<u8> {
+    fn wipe(&mut self) {
+        wipe(self);
+    }
+}
+
+impl Wipe for Totp<'_> {
+    fn wipe(&mut self) {
+        self.hotp.wipe_secret();
+    }
+}
+
+/// Wipes the value it wraps when dropped.
+struct WipeOnDrop<T: Wipe>(T);
+
+impl<T: Wipe> Drop for WipeOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EncryptedSecret, SecretCipher, SecretProvider, Wipe};
+    use crate::totp::{CreateOption, Totp};
+    use hmacsha::ShaTypes;
+    use std::panic::{self, AssertUnwindSafe};
+
+    const SECRET: &[u8] = b"12345678901234567890";
+    const KEY: u8 = 0xa5;
+
+    /// A toy cipher, XOR with `KEY` after a one-byte tag of `KEY`, standing in for AES-GCM.
+    struct XorCipher;
+
+    #[derive(Debug, PartialEq)]
+    struct BadTag;
+
+    impl SecretCipher for XorCipher {
+        type Error = BadTag;
+
+        fn decrypt(&self, ciphertext: &[u8], plaintext: &mut Vec<u8>) -> Result<(), BadTag> {
+            match ciphertext.split_first() {
+                Some((&KEY, body)) => {
+                    plaintext.extend(body.iter().map(|byte| byte ^ KEY));
+                    Ok(())
+                }
+                _ => Err(BadTag),
+            }
+        }
+    }
+
+    fn encrypted(secret: &[u8]) -> EncryptedSecret<XorCipher> {
+        let mut ciphertext = vec![KEY];
+        ciphertext.extend(secret.iter().map(|byte| byte ^ KEY));
+        EncryptedSecret {
+            ciphertext,
+            cipher: XorCipher,
+        }
+    }
+
+    #[test]
+    fn encrypted_secret_parity_test() {
+        let secret = encrypted(SECRET);
+        assert_eq!(
+            secret.with_secret(|plaintext| plaintext.to_vec()),
+            Ok(SECRET.to_vec())
+        );
+        let option = CreateOption::Full {
+            digits: 8,
+            period: 30,
+            algorithm: &ShaTypes::Sha1,
+        };
+        let plain = Totp::secret(SECRET.to_vec(), option).with_salt(b"account-1");
+        let totp = Totp::secret(Vec::new(), option).with_salt(b"account-1");
+        for &time in [59, 1_111_111_109, 1_234_567_890].iter() {
+            let otp = totp.with_secret_from(&secret, |totp| totp.make_time(time));
+            assert_eq!(otp, Ok(plain.make_time(time)));
+        }
+        let otp = plain.make();
+        assert_eq!(
+            totp.with_secret_from(&secret, |totp| totp.check(&otp, None)),
+            Ok(true)
+        );
+        assert!(totp.hotp.secret().is_empty());
+    }
+
+    #[test]
+    fn encrypted_secret_error_test() {
+        let mut secret = encrypted(SECRET);
+        secret.ciphertext[0] ^= 1;
+        let totp = Totp::secret(Vec::new(), CreateOption::Default);
+        assert_eq!(
+            totp.with_secret_from(&secret, |totp| totp.make()),
+            Err(BadTag)
+        );
+    }
+
+    #[test]
+    fn encrypted_secret_wipe_test() {
+        let secret = encrypted(SECRET);
+        let mut plaintext = Vec::new();
+        let seen = secret.with_secret_in(&mut plaintext, |plaintext| plaintext.to_vec());
+        assert_eq!(seen, Ok(SECRET.to_vec()));
+        assert_eq!(plaintext, vec![0; SECRET.len()]);
+
+        let mut plaintext = Vec::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            secret.with_secret_in(&mut plaintext, |_| panic!("boom"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(plaintext, vec![0; SECRET.len()]);
+
+        let mut totp = Totp::secret(SECRET.to_vec(), CreateOption::Default);
+        totp.wipe();
+        assert_eq!(totp.hotp.secret(), vec![0; SECRET.len()]);
+    }
+}
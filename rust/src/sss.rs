@@ -0,0 +1,154 @@
+use std::fmt;
+
+/// The errors returned when combining Shamir secret shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShareError {
+    /// Fewer shares than the threshold were provided.
+    NotEnoughShares,
+    /// A share is empty or its length differs from the other shares.
+    InvalidLength,
+    /// A share uses the x coordinate `0`, or two shares use the same x coordinate.
+    InvalidIndex,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::NotEnoughShares => write!(f, "not enough shares to reach the threshold"),
+            ShareError::InvalidLength => write!(f, "shares have inconsistent lengths"),
+            ShareError::InvalidIndex => write!(f, "shares have invalid or duplicate indices"),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// Multiplies two elements of GF(2^8), reduced by the AES polynomial `x^8 + x^4 + x^3 + x + 1`.
+///
+/// Runs in constant time: the bits of the share bytes select terms through masks, not
+/// branches.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// Returns the multiplicative inverse in GF(2^8), `a^254`.
+fn gf_inv(a: u8) -> u8 {
+    (0..253).fold(a, |acc, _| gf_mul(acc, a))
+}
+
+/**
+Reconstructs a secret split with Shamir's secret sharing over GF(2^8).
+
+Each share is its x coordinate (non-zero) followed by one y byte per secret byte.
+Only the first `threshold` shares are used. The secret is computed directly into the
+returned buffer, without intermediate copies. Neither the shares nor the returned secret
+are wiped: both belong to the caller.
+
+# Example
+
+```
+use ootp::sss::combine;
+
+// Shares of the secret "ab" with a threshold of 2.
+let shares = [vec![1, 0x60, 0x63], vec![2, 0x63, 0x60]];
+assert_eq!(combine(&shares, 2), Ok(b"ab".to_vec()));
+```
+*/
+pub fn combine(shares: &[Vec<u8>], threshold: u8) -> Result<Vec<u8>, ShareError> {
+    let threshold = usize::from(threshold).max(1);
+    if shares.len() < threshold {
+        return Err(ShareError::NotEnoughShares);
+    }
+    let shares = &shares[..threshold];
+    let len = shares[0].len();
+    if len < 2 || shares.iter().any(|share| share.len() != len) {
+        return Err(ShareError::InvalidLength);
+    }
+    let xs: Vec<u8> = shares.iter().map(|share| share[0]).collect();
+    if xs
+        .iter()
+        .enumerate()
+        .any(|(i, &x)| x == 0 || xs[..i].contains(&x))
+    {
+        return Err(ShareError::InvalidIndex);
+    }
+
+    // Lagrange basis polynomials evaluated at x = 0.
+    let basis: Vec<u8> = xs
+        .iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            xs.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(1, |acc, (_, &xj)| gf_mul(acc, gf_mul(xj, gf_inv(xj ^ xi))))
+        })
+        .collect();
+    let secret = (1..len)
+        .map(|byte| {
+            shares
+                .iter()
+                .zip(&basis)
+                .fold(0, |acc, (share, &l)| acc ^ gf_mul(share[byte], l))
+        })
+        .collect();
+    Ok(secret)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{combine, gf_inv, gf_mul, ShareError};
+
+    /// Splits `secret` into shares `1..=count`, using `coefficients` as the
+    /// `threshold - 1` random polynomial coefficients of every byte.
+    pub(crate) fn split(secret: &[u8], coefficients: &[u8], count: u8) -> Vec<Vec<u8>> {
+        (1..=count)
+            .map(|x| {
+                let mut share = vec![x];
+                share.extend(secret.iter().map(|&byte| {
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, &c| gf_mul(acc ^ c, x))
+                        ^ byte
+                }));
+                share
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gf_inverse_test() {
+        for a in 1..=255 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn combine_test() {
+        let secret = "12345678901234567890".as_bytes();
+        let shares = split(secret, &[0x53, 0xca], 5);
+        assert_eq!(combine(&shares, 3).unwrap(), secret);
+        assert_eq!(combine(&shares[2..], 3).unwrap(), secret);
+        let subset = vec![shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        assert_eq!(combine(&subset, 3).unwrap(), secret);
+        assert_ne!(combine(&shares, 2).unwrap(), secret);
+    }
+
+    #[test]
+    fn combine_errors_test() {
+        let shares = split(b"secret", &[0x11], 3);
+        assert_eq!(combine(&shares[..1], 2), Err(ShareError::NotEnoughShares));
+        let duplicate = vec![shares[0].clone(), shares[0].clone()];
+        assert_eq!(combine(&duplicate, 2), Err(ShareError::InvalidIndex));
+        let truncated = vec![shares[0].clone(), shares[1][..3].to_vec()];
+        assert_eq!(combine(&truncated, 2), Err(ShareError::InvalidLength));
+    }
+}
@@ -1,27 +1,120 @@
-use crate::constants::{DEFAULT_ALGORITHM, DEFAULT_DIGITS, DEFAULT_PERIOD};
-use crate::hotp::{CheckOption, Hotp, MakeOption};
+use crate::algorithm::algorithm_label;
+use crate::clock::{SystemClock, TimeSource};
+use crate::constants::{
+    DEFAULT_ALGORITHM, DEFAULT_DIGITS, DEFAULT_PERIOD, DEFAULT_RADIX, MAX_OTP_INPUT_LEN,
+};
+use crate::encoding::{decode_auto, decode_base32, DecodeError};
+use crate::hotp::{constant_time_eq, format_code_radix, normalize, CheckOption, Hotp};
 use hmacsha::ShaTypes;
-use std::time::SystemTime;
-
-fn get_unix_epoch() -> u64 {
-    SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-}
-fn create_counter(period: u64) -> u64 {
-    get_unix_epoch() / period
-}
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
 
 /// The TOTP is a HOTP-based one-time password algorithm, with a time value as moving factor.
 ///
 /// It takes four parameter. An `Hotp` istance, the desired number of digits, a time period and the SHA algorithm.
+/// The current time is read from `clock`, the `SystemClock` by default.
+/// A `period` of zero is treated as one second.
+/// Every step is offset by `fixed_drift` steps, `0` by default.
+/// Codes are written in base `radix`, `10` unless configured otherwise.
+/// With `allow_stripped_leading_zeros`, off by default, codes shorter than `digits` are
+/// left-padded with zeros before verification.
+/// With a `window_policy`, `check` applies it when no explicit breadth is given.
+///
+/// Its `Display` output is the current one-time password, reading the clock each time it is
+/// formatted. Its `Debug` output redacts the secret.
 pub struct Totp<'a> {
     pub hotp: Hotp,
     pub digits: u32,
     pub period: u64,
     pub algorithm: &'a ShaTypes,
+    pub clock: &'a dyn TimeSource,
+    pub fixed_drift: i64,
+    pub radix: u32,
+    pub allow_stripped_leading_zeros: bool,
+    pub window_policy: Option<SkewPolicy>,
+}
+
+impl fmt::Display for Totp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.make())
+    }
+}
+
+impl fmt::Debug for Totp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Totp")
+            .field("hotp", &self.hotp)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("algorithm", &algorithm_label(self.algorithm))
+            .field("fixed_drift", &self.fixed_drift)
+            .field("radix", &self.radix)
+            .field(
+                "allow_stripped_leading_zeros",
+                &self.allow_stripped_leading_zeros,
+            )
+            .field("window_policy", &self.window_policy)
+            .finish()
+    }
+}
+
+/// The parameters of a TOTP, independent of its secret.
+///
+/// A `radix` other than `10` is a non-standard extension, unsupported by authenticator apps.
+#[derive(Clone, Copy)]
+pub struct OtpConfig<'a> {
+    pub digits: u32,
+    pub period: u64,
+    pub algorithm: &'a ShaTypes,
+    pub radix: u32,
+}
+
+impl Default for OtpConfig<'_> {
+    fn default() -> Self {
+        Self {
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+            algorithm: DEFAULT_ALGORITHM,
+            radix: DEFAULT_RADIX,
+        }
+    }
+}
+
+impl PartialEq for OtpConfig<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digits == other.digits
+            && self.period == other.period
+            && algorithm_label(self.algorithm) == algorithm_label(other.algorithm)
+            && self.radix == other.radix
+    }
+}
+
+impl Eq for OtpConfig<'_> {}
+
+impl fmt::Debug for OtpConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtpConfig")
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("algorithm", &algorithm_label(self.algorithm))
+            .field("radix", &self.radix)
+            .finish()
+    }
+}
+
+/// A reusable clock skew tolerance, in steps, for `Totp::check_policy`.
+///
+/// With `reject_future`, codes of future steps are rejected whatever `forward_steps` is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkewPolicy {
+    pub back_steps: u64,
+    pub forward_steps: u64,
+    pub reject_future: bool,
 }
+
 /// The Options for the TOTP's `make` function.
 #[derive(Clone, Copy)]
 pub enum CreateOption<'a> {
@@ -41,6 +134,130 @@ pub enum CreateOption<'a> {
     Algorithm(&'a ShaTypes),
 }
 
+/// The outcome of `Totp::diagnose`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnoseResult {
+    /// The code matched the step `drift` steps away from the current one.
+    Matched { drift: i64 },
+    /// No step between `first_counter` and `last_counter` (inclusive) produced the code.
+    NotFound {
+        first_counter: u64,
+        last_counter: u64,
+    },
+}
+
+/// The outcome of `Totp::verify_detailed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyResult {
+    /// The code is valid, `drift` steps away from the current one, in the past or present.
+    Valid { drift: i64 },
+    /// The code is valid but matches a step `steps` steps in the future: the submitter's
+    /// clock is likely fast and should be synchronized. The code is accepted.
+    FutureMatch { steps: u64 },
+    /// The code would be valid if the `Totp` was configured with `submitted_len` digits.
+    DigitCountMismatch {
+        submitted_len: usize,
+        expected_len: usize,
+    },
+    /// The code is not valid.
+    Invalid,
+}
+
+impl VerifyResult {
+    /// Returns a boolean indicating if the code is accepted, possibly with a warning.
+    pub const fn is_accepted(&self) -> bool {
+        matches!(
+            self,
+            VerifyResult::Valid { .. } | VerifyResult::FutureMatch { .. }
+        )
+    }
+}
+
+/// The codes returned by `Totp::valid_codes_at`, redacted in `Debug` and `Display`
+/// outputs so that they are not logged by accident.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ValidCodes(Vec<String>);
+
+impl ValidCodes {
+    /// Returns the codes, in ascending counter order.
+    pub fn reveal(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ValidCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ValidCodes(REDACTED, {} codes)", self.0.len())
+    }
+}
+
+impl fmt::Display for ValidCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} REDACTED codes", self.0.len())
+    }
+}
+
+/// The details of a matched code passed to the policy of `Totp::check_with_policy_fn`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyContext {
+    /// The drift, in steps, of the matched code.
+    pub drift: i64,
+    /// The counter of the matched code.
+    pub counter: u64,
+    /// The Unix time, in seconds, at which the matched step starts.
+    pub matched_time: u64,
+    /// The Unix time, in seconds, of the verification.
+    pub now: u64,
+}
+
+/// The errors returned by `Totp::self_test`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// Two computations of the same code differ.
+    Inconsistent,
+    /// The generated code does not have the configured number of digits.
+    MalformedCode,
+    /// The generated code is rejected by the verification.
+    Rejected,
+}
+
+impl std::fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelfTestError::Inconsistent => write!(f, "the HMAC backend is not deterministic"),
+            SelfTestError::MalformedCode => write!(f, "the generated code is malformed"),
+            SelfTestError::Rejected => write!(f, "the generated code does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for SelfTestError {}
+
+/// The errors returned by `Totp::verify_freshness`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreshnessError {
+    /// The claimed time is more than the maximum age in the past.
+    Stale { age: u64 },
+    /// The claimed time is more than the allowed skew in the future.
+    FromFuture { ahead: u64 },
+    /// The code does not match the step of the claimed time.
+    Invalid,
+}
+
+impl std::fmt::Display for FreshnessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FreshnessError::Stale { age } => write!(f, "the code is {}s old", age),
+            FreshnessError::FromFuture { ahead } => {
+                write!(f, "the code is claimed {}s in the future", ahead)
+            }
+            FreshnessError::Invalid => write!(f, "the code does not match the claimed time"),
+        }
+    }
+}
+
+impl std::error::Error for FreshnessError {}
+
 impl<'a> Totp<'a> {
     /// TOTP instance "private" constructor
     const fn new(hotp: Hotp, digits: u32, period: u64, algorithm: &'a ShaTypes) -> Self {
@@ -49,6 +266,11 @@ impl<'a> Totp<'a> {
             digits,
             period,
             algorithm,
+            clock: &SystemClock,
+            fixed_drift: 0,
+            radix: DEFAULT_RADIX,
+            allow_stripped_leading_zeros: false,
+            window_policy: None,
         }
     }
 
@@ -68,155 +290,2469 @@ impl<'a> Totp<'a> {
         };
         Totp::new(hotp, digits, period, algorithm)
     }
+
     /**
-    This function returns a string of the one-time password
+    TOTP instance constructor with FreeOTP's default configuration: SHA1, 6 digits and
+    a 30 seconds period. It does not depend on the crate's defaults.
 
     # Example
 
-    ```rust
-    use ootp::totp::{Totp, CreateOption};
+    ```
+    use ootp::totp::Totp;
 
-    let secret = "A strong shared secrett".as_bytes().to_vec();
-    let totp = Totp::secret(
-        secret,
-        CreateOption::Default
-    );
+    let totp = Totp::freeotp_default("12345678901234567890".as_bytes().to_vec());
+    assert_eq!(totp.make_time(59), "287082");
+    ```
+    */
+    pub const fn freeotp_default(secret: Vec<u8>) -> Totp<'a> {
+        Totp::new(Hotp::new(secret), 6, 30, &ShaTypes::Sha1)
+    }
+
+    /**
+    TOTP instance constructor from an `OtpConfig`.
+
+    # Example
 
-    let otp = totp.make(0); // Generate a one-time password
-    println!("{}", otp); // Print the one-time password
     ```
+    use ootp::totp::{OtpConfig, Totp};
 
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let config = OtpConfig { radix: 16, ..OtpConfig::default() };
+    let totp = Totp::from_config(secret, config);
+    assert_eq!(totp.config(), config);
+    ```
     */
+    pub fn from_config(secret: Vec<u8>, config: OtpConfig<'a>) -> Totp<'a> {
+        Totp::new(
+            Hotp::new(secret),
+            config.digits,
+            config.period,
+            config.algorithm,
+        )
+        .with_radix(config.radix)
+    }
 
-    /**
-     * steps_drift: 時間ステップのドリフト値を指定する。
-     *  steps_drift*self.digits秒単位でタイムステップがズレた状態のカウンタを生成する。
-     *  0であればドリフトなし。
-     */
-    pub fn make(&self) -> String {
-        self.hotp.make(MakeOption::Full {
-            counter: create_counter(self.period),
+    /// Returns the `OtpConfig` of this `Totp`.
+    pub fn config(&self) -> OtpConfig<'a> {
+        OtpConfig {
             digits: self.digits,
+            period: self.period,
             algorithm: self.algorithm,
-        })
+            radix: self.radix,
+        }
     }
 
     /**
-    This function returns a string of the one-time password, valid a `period` from `time` seconds since the UNIX epoch
+    Returns a human-readable summary of the configuration, without the secret, e.g. for
+    logs and support. The radix is mentioned only when it is not decimal.
 
     # Example
 
-    ```rust
+    ```
     use ootp::totp::{Totp, CreateOption};
 
-    let secret = "A strong shared secrett".as_bytes().to_vec();
-    let totp = Totp::secret(
-        secret,
-        CreateOption::Default
-    );
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.config_summary(), "TOTP SHA1, 6 digits, 30s period");
+    ```
+    */
+    pub fn config_summary(&self) -> String {
+        let mut summary = format!(
+            "TOTP {}, {} digits, {}s period",
+            algorithm_label(self.algorithm),
+            self.digits,
+            self.period
+        );
+        if self.radix != DEFAULT_RADIX {
+            summary.push_str(&format!(", base {}", self.radix));
+        }
+        summary
+    }
+
+    /**
+    Writes the codes in base `radix` (`2..=36`, lowercase letters above 9) instead of decimal.
+
+    This is a non-standard extension: both the generating and the verifying side must use
+    the same radix, and authenticator apps only support decimal codes.
+
+    # Example
 
-    let otp = totp.make_time(59); // Generate a one-time password, valid a `DEFAULT_PERIOD from `59` seconds since the UNIX epoch
-    println!("{}", otp); // Print the one-time password
     ```
+    use ootp::totp::{Totp, CreateOption};
 
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default).with_radix(16);
+    assert!(totp.check(&totp.make(), Some(1)));
+    ```
     */
-    pub fn make_time(&self, time: u64) -> String {
-        self.hotp.make(MakeOption::Full {
-            counter: time / self.period,
-            digits: self.digits,
-            algorithm: self.algorithm,
+    pub fn with_radix(mut self, radix: u32) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    /**
+    Accepts codes whose leading zeros were stripped by a broken client, e.g. "12345"
+    for "012345", by left-padding shorter numeric codes with zeros to `digits` digits.
+
+    This is non-standard and slightly increases the chances of guessing a code.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default).with_stripped_leading_zeros(true);
+    let otp = totp.make();
+    assert!(totp.verify_strict(otp.trim_start_matches('0')));
+    ```
+    */
+    pub fn with_stripped_leading_zeros(mut self, allow: bool) -> Self {
+        self.allow_stripped_leading_zeros = allow;
+        self
+    }
+
+    /**
+    Sets the clock skew tolerance `check` applies when no explicit breadth is given.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption, SkewPolicy};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let policy = SkewPolicy { back_steps: 1, forward_steps: 0, reject_future: true };
+    let totp = Totp::secret(secret, CreateOption::Default).with_window_policy(policy);
+    assert!(totp.check(&totp.make_drift(-1), None));
+    assert!(!totp.check(&totp.make_drift(1), None));
+    ```
+    */
+    pub fn with_window_policy(mut self, policy: SkewPolicy) -> Self {
+        self.window_policy = Some(policy);
+        self
+    }
+
+    /**
+    TOTP instance constructor from Shamir secret shares, see `ootp::sss::combine`.
+
+    The reconstructed secret is moved into the `Totp`, without intermediate copies, and
+    overwritten with zeros when the `Totp` is dropped. The shares are not wiped: they
+    belong to the caller.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let shares = [vec![1, 0x60, 0x63], vec![2, 0x63, 0x60]];
+    let totp = Totp::from_shares(&shares, 2, CreateOption::Default).unwrap();
+    assert_eq!(totp.hotp.secret(), b"ab".to_vec());
+    ```
+    */
+    #[cfg(feature = "sss")]
+    pub fn from_shares(
+        shares: &[Vec<u8>],
+        threshold: u8,
+        option: CreateOption<'a>,
+    ) -> Result<Totp<'a>, crate::sss::ShareError> {
+        crate::sss::combine(shares, threshold).map(|secret| {
+            let totp = Totp::secret(secret, option);
+            Totp {
+                hotp: totp.hotp.wipe_on_drop(),
+                ..totp
+            }
         })
     }
+
     /**
-     * steps_drift: 時間ステップのドリフト値を指定する。
-     *  steps_drift*self.digits秒単位でタイムステップがズレた状態のカウンタを生成する。
-     *  0であればドリフトなし。
-     */
-    pub fn make_drift(&self, steps_drift: i64) -> String {
-        self.make_time(
-            (get_unix_epoch() as i128 + (self.period as i128 * steps_drift as i128)) as u64,
-        )
+    Offsets every generated and verified step by `steps`, for tokens shipped with a known
+    clock drift: a token running 3 steps behind validates with a fixed drift of `-3`
+    without widening the verification window.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let token = Totp::secret(secret.clone(), CreateOption::Default);
+    let totp = Totp::secret(secret, CreateOption::Default).with_fixed_drift(-3);
+    assert!(totp.check(&token.make_drift(-3), Some(0)));
+    ```
+    */
+    pub fn with_fixed_drift(mut self, steps: i64) -> Self {
+        self.fixed_drift = steps;
+        self
     }
 
     /**
-    Returns a boolean indicating if the one-time password is valid.
+    Sets the per-account salt mixed into the counter message, see `Hotp::with_salt`.
+    Codes verify only with the salt they were made with.
 
-    # Example #1
+    # Example
 
     ```
     use ootp::totp::{Totp, CreateOption};
 
     let secret = "A strong shared secret".as_bytes().to_vec();
-    let totp = Totp::secret(
-        secret,
-        CreateOption::Default
-    );
-    let otp = totp.make(0); // Generate a one-time password
-    let check = totp.check(otp.as_str(), None);
+    let totp = Totp::secret(secret.clone(), CreateOption::Default).with_salt(b"account-42");
+    let other = Totp::secret(secret, CreateOption::Default).with_salt(b"account-43");
+    assert!(totp.check(&totp.make(), None));
+    assert_ne!(totp.make(), other.make());
     ```
+    */
+    pub fn with_salt(mut self, salt: impl AsRef<[u8]>) -> Self {
+        self.hotp = self.hotp.with_salt(salt);
+        self
+    }
 
-    # Example #2
+    /**
+    TOTP instance constructor from a Base32 encoded secret, as shown by authenticator apps.
+    The secret is case-insensitive and whitespace is ignored, e.g. between groups of
+    4 characters.
+
+    A secret that does not decode to a whole number of bytes is rejected with
+    `DecodeError::InvalidLength`, a character outside of the alphabet with
+    `DecodeError::InvalidCharacter`.
 
+    # Example
+
+    ```
+    use ootp::encoding::DecodeError;
+    use ootp::totp::{Totp, CreateOption};
+
+    let totp = Totp::from_base32("JBSWY3DPEHPK3PXP", CreateOption::Default).unwrap();
+    assert_eq!(totp.hotp.secret(), b"Hello!\xDE\xAD\xBE\xEF");
+    let grouped = Totp::from_base32("jbsw y3dp ehpk 3pxp", CreateOption::Default).unwrap();
+    assert_eq!(grouped.hotp.secret_base32(), "JBSWY3DPEHPK3PXP");
+    assert!(matches!(
+        Totp::from_base32("JBSWY3DPEHPK3PX", CreateOption::Default),
+        Err(DecodeError::InvalidLength)
+    ));
+    ```
+    */
+    pub fn from_base32(secret: &str, option: CreateOption<'a>) -> Result<Totp<'a>, DecodeError> {
+        let secret: String = secret.chars().filter(|c| !c.is_whitespace()).collect();
+        decode_base32(&secret).map(|secret| Totp::secret(secret, option))
+    }
+
+    /**
+    TOTP instance constructor from a hexadecimal or Base32 encoded secret,
+    detected as documented by `ootp::encoding::decode_auto`.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let totp = Totp::from_auto("JBSWY3DPEHPK3PXP", CreateOption::Default).unwrap();
+    assert_eq!(totp.hotp.secret(), b"Hello!\xDE\xAD\xBE\xEF");
     ```
+    */
+    pub fn from_auto(secret: &str, option: CreateOption<'a>) -> Result<Totp<'a>, DecodeError> {
+        decode_auto(secret).map(|secret| Totp::secret(secret, option))
+    }
+
+    /**
+    Replaces the `TimeSource` used to read the current time.
+
+    # Example
+
+    ```
+    use ootp::clock::SystemClock;
     use ootp::totp::{Totp, CreateOption};
 
     let secret = "A strong shared secret".as_bytes().to_vec();
-    let totp = Totp::secret(
-        secret,
-        CreateOption::Digits(8)
-    );
-    let otp = totp.make(0); // Generate a one-time password
-    let check = totp.check(otp.as_str(), Some(42));
+    let totp = Totp::secret(secret, CreateOption::Default).with_clock(&SystemClock);
     ```
     */
-    pub fn check(&self, otp: &str, breadth: Option<u64>) -> bool {
-        self.hotp.check(
-            otp,
-            CheckOption::Full {
-                counter: create_counter(self.period),
-                breadth: breadth.unwrap_or(DEFAULT_PERIOD),
-                algorithm: self.algorithm,
-            },
-        )
+    pub fn with_clock(mut self, clock: &'a dyn TimeSource) -> Self {
+        self.clock = clock;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{CreateOption, Totp};
-    use crate::constants::{self, DEFAULT_DIGITS};
+    fn counter(&self) -> u64 {
+        self.step_at(self.clock.now_secs())
+    }
 
-    #[test]
-    fn it_works() {
-        let secret = "A strong shared secret".as_bytes().to_vec();
-        let totp = Totp::secret(secret, CreateOption::Default);
-        let code = totp.make(0);
-        assert_eq!(code.len(), DEFAULT_DIGITS as usize);
+    /// Returns the step of `time`, offset by the fixed drift.
+    fn step_at(&self, time: u64) -> u64 {
+        (time / self.period()).saturating_add_signed(self.fixed_drift)
     }
 
-    /// Taken from [RFC 6238](https://datatracker.ietf.org/doc/html/rfc6238#appendix-B)
-    #[test]
-    fn make_test_correcteness() {
-        let secret = "12345678901234567890".as_bytes().to_vec();
-        let totp = Totp::secret(secret, CreateOption::Digits(8));
-        let code = totp.make_time(59);
-        assert_eq!(code, "94287082");
-        let code = totp.make_time(1_111_111_109);
-        assert_eq!(code, "07081804");
-        let code = totp.make_time(1_111_111_111);
-        assert_eq!(code, "14050471");
-        let code = totp.make_time(1_234_567_890);
-        assert_eq!(code, "89005924");
-        let code = totp.make_time(2_000_000_000);
-        assert_eq!(code, "69279037");
-        let code = totp.make_time(20_000_000_000);
-        assert_eq!(code, "65353130");
+    fn period(&self) -> u64 {
+        self.period.max(1)
     }
+    /**
+    This function returns a string of the one-time password
 
-    /// Taken from [RFC 6238](https://datatracker.ietf.org/doc/html/rfc6238#appendix-B)
-    /// Errata for [RFC 6238]](https://www.rfc-editor.org/errata_search.php?rfc=6238&rec_status=0)
-    #[test]
-    fn make_test_correcteness_sha256() {
+    # Example
+
+    ```rust
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secrett".as_bytes().to_vec();
+    let totp = Totp::secret(
+        secret,
+        CreateOption::Default
+    );
+
+    let otp = totp.make(); // Generate a one-time password
+    println!("{}", otp); // Print the one-time password
+    ```
+
+    */
+    pub fn make(&self) -> String {
+        self.code_at(self.counter(), self.digits)
+    }
+
+    /**
+    Generates a code that will pass `check()` right now, e.g. to feed a verification in the
+    tests of an application. It is equal to `make()`: there is no argument to pass,
+    in particular no counter.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    // In a `#[cfg(test)]` module of your application:
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let code = totp.current_valid_code();
+    assert!(totp.check(&code, None));
+    ```
+    */
+    pub fn current_valid_code(&self) -> String {
+        self.make()
+    }
+
+    /**
+    Returns the one-time password for the current time, to be shown as the reference code
+    while verifying an enrollment. It is the same code the user's authenticator app displays
+    when configured with this `Totp`'s digits, period and algorithm.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let code = totp.enroll_code();
+    assert!(totp.check(&code, Some(1)));
+    ```
+    */
+    pub fn enroll_code(&self) -> String {
+        self.make()
+    }
+
+    /**
+    This function returns a string of the one-time password, valid a `period` from `time` seconds since the UNIX epoch
+
+    # Example
+
+    ```rust
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secrett".as_bytes().to_vec();
+    let totp = Totp::secret(
+        secret,
+        CreateOption::Default
+    );
+
+    let otp = totp.make_time(59); // Generate a one-time password, valid a `DEFAULT_PERIOD from `59` seconds since the UNIX epoch
+    println!("{}", otp); // Print the one-time password
+    ```
+
+    */
+    pub fn make_time(&self, time: u64) -> String {
+        self.code_at(self.step_at(time), self.digits)
+    }
+    /**
+    Returns the current one-time password along with the inclusive range of Unix times,
+    in seconds, during which it is the current one. The clock is sampled only once.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let (otp, validity) = totp.make_with_validity();
+    assert_eq!(totp.make_time(*validity.start()), otp);
+    assert_eq!(validity.end() - validity.start() + 1, 30);
+    ```
+    */
+    pub fn make_with_validity(&self) -> (String, RangeInclusive<u64>) {
+        let time = self.clock.now_secs();
+        let start = time - time % self.period();
+        let end = start.saturating_add(self.period() - 1);
+        (self.make_time(time), start..=end)
+    }
+
+    /**
+    Returns the one-time passwords of the current and the next `n - 1` periods, each with
+    the Unix time, in seconds, at which it becomes current. The clock is sampled only once.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let codes = totp.upcoming_codes(10);
+    assert_eq!(codes.len(), 10);
+    assert_eq!(codes[1].0 - codes[0].0, 30);
+    ```
+    */
+    pub fn upcoming_codes(&self, n: usize) -> Vec<(u64, String)> {
+        let time = self.clock.now_secs();
+        let current = time / self.period();
+        (0..n as u64)
+            .map_while(|i| current.checked_add(i)?.checked_mul(self.period()))
+            .map(|start| (start, self.make_time(start)))
+            .collect()
+    }
+
+    /**
+    Returns the one-time passwords of the times from `start` to `end` inclusive, each with
+    the inclusive range of times, clipped to `start..=end`, during which it is current.
+    Consecutive periods sharing the same code are collapsed into a single entry.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let codes = totp.distinct_codes_in(0, 119);
+    assert_eq!(codes.len(), 4);
+    assert_eq!(codes[1], (30..=59, totp.make_time(30)));
+    ```
+    */
+    pub fn distinct_codes_in(&self, start: u64, end: u64) -> Vec<(RangeInclusive<u64>, String)> {
+        let period = self.period();
+        let mut codes: Vec<(RangeInclusive<u64>, String)> = Vec::new();
+        if start > end {
+            return codes;
+        }
+        for index in start / period..=end / period {
+            let period_start = (index * period).max(start);
+            let period_end = (index * period).saturating_add(period - 1).min(end);
+            let code = self.make_time(period_start);
+            match codes.last_mut() {
+                Some((range, last)) if *last == code => *range = *range.start()..=period_end,
+                _ => codes.push((period_start..=period_end, code)),
+            }
+        }
+        codes
+    }
+
+    /**
+    Returns the one-time password of the current time shifted by `days` whole days of
+    86400 seconds, e.g. `-1` for yesterday and `1` for tomorrow.
+
+    The time is shifted, not the counter, so the configured period still applies: with a
+    86400 seconds period this is the code of the calendar day (in UTC) `days` away, with
+    shorter periods the code of the same time of that day. Saturates at the representable
+    times.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Period(86_400));
+    assert_eq!(totp.make_days_offset(0), totp.make());
+    ```
+    */
+    pub fn make_days_offset(&self, days: i64) -> String {
+        const SECONDS_PER_DAY: i128 = 86_400;
+        let time = self.clock.now_secs() as i128 + SECONDS_PER_DAY * days as i128;
+        self.make_time(time.clamp(0, i128::from(u64::MAX)) as u64)
+    }
+
+    /**
+    Returns the current one-time password rendered as large block digits, e.g. for a kiosk
+    display: 5 lines of 3 columns wide glyphs separated by a space, without a trailing
+    newline. Characters other than decimal digits are drawn on the middle line.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.make_ascii_art().lines().count(), 5);
+    ```
+    */
+    pub fn make_ascii_art(&self) -> String {
+        ascii_art(&self.make())
+    }
+
+    /**
+     * steps_drift: 時間ステップのドリフト値を指定する。
+     *  steps_drift*self.digits秒単位でタイムステップがズレた状態のカウンタを生成する。
+     *  0であればドリフトなし。
+     */
+    pub fn make_drift(&self, steps_drift: i64) -> String {
+        let time = self.clock.now_secs() as i128 + (self.period() as i128 * steps_drift as i128);
+        self.make_time(time.clamp(0, i128::from(u64::MAX)) as u64)
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is valid.
+
+    Without an explicit `breadth`, the `window_policy` applies if one is set.
+
+    # Example #1
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(
+        secret,
+        CreateOption::Default
+    );
+    let otp = totp.make(); // Generate a one-time password
+    let check = totp.check(otp.as_str(), None);
+    ```
+
+    # Example #2
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(
+        secret,
+        CreateOption::Digits(8)
+    );
+    let otp = totp.make(); // Generate a one-time password
+    let check = totp.check(otp.as_str(), Some(42));
+    ```
+    */
+    pub fn check(&self, otp: &str, breadth: Option<u64>) -> bool {
+        if let (None, Some(policy)) = (breadth, &self.window_policy) {
+            return self.check_policy(otp, policy);
+        }
+        let otp = self.normalize_code(otp);
+        let breadth = breadth.unwrap_or(DEFAULT_PERIOD);
+        self.drift_with_digits(&otp, self.clock.now_secs(), breadth, otp.len() as u32)
+            .is_some()
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is valid within `tolerance`
+    of the current time, rounded down to whole steps.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+    use std::time::Duration;
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.check_duration(&totp.make_drift(-1), Duration::from_secs(60)));
+    ```
+    */
+    pub fn check_duration(&self, otp: &str, tolerance: Duration) -> bool {
+        self.drift_at(
+            otp,
+            self.clock.now_secs(),
+            tolerance.as_secs() / self.period(),
+        )
+        .is_some()
+    }
+
+    /**
+    Returns the number of seconds before the current one-time password expires.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let remaining = totp.remaining();
+    assert!(remaining >= 1 && remaining <= 30);
+    ```
+    */
+    pub fn remaining(&self) -> u64 {
+        self.remaining_at(self.clock.now_secs())
+    }
+
+    /**
+    Returns the number of milliseconds before the current one-time password expires,
+    from the sub-second time of the `TimeSource`, e.g. for token caching layers.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let remaining = totp.remaining_millis();
+    assert!(remaining >= 1 && remaining <= 30_000);
+    ```
+    */
+    pub fn remaining_millis(&self) -> u128 {
+        let period = u128::from(self.period()) * 1000;
+        period - self.clock.now_millis() % period
+    }
+
+    /**
+    Returns a boolean indicating if the current one-time password expires within
+    `within_secs` seconds, e.g. to suggest waiting for the next one.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.is_near_rollover(30));
+    ```
+    */
+    pub fn is_near_rollover(&self, within_secs: u64) -> bool {
+        self.remaining() <= within_secs
+    }
+
+    /**
+    Returns the `Instant` at which the current one-time password expires,
+    e.g. to schedule the refresh of a displayed code.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+    use std::time::{Duration, Instant};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.next_rollover_instant() <= Instant::now() + Duration::from_secs(30));
+    ```
+    */
+    pub fn next_rollover_instant(&self) -> Instant {
+        Instant::now() + Duration::from_secs(self.remaining())
+    }
+
+    /**
+    Returns the number of seconds until the step `counter` becomes the current one,
+    or `None` if that step is already in the past.
+
+    The current step yields `Some(0)`. The clock is sampled only once.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.seconds_until_counter(0), None);
+    ```
+    */
+    pub fn seconds_until_counter(&self, counter: u64) -> Option<u64> {
+        self.seconds_until_counter_at(counter, self.clock.now_secs())
+    }
+
+    /**
+    Returns the index of the first `Totp` of `history` accepting `otp` within `window` steps
+    of the current time, or `None` if no historical secret matches.
+
+    Each candidate is compared in constant time.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let current = Totp::secret("Current secret".as_bytes().to_vec(), CreateOption::Default);
+    let history = [
+        Totp::secret("Previous secret".as_bytes().to_vec(), CreateOption::Default),
+        Totp::secret("Oldest secret".as_bytes().to_vec(), CreateOption::Default),
+    ];
+    let otp = history[1].make();
+    assert_eq!(current.verify_history(&otp, &history, 1), Some(1));
+    ```
+    */
+    pub fn verify_history(&self, otp: &str, history: &[Totp], window: u64) -> Option<usize> {
+        let time = self.clock.now_secs();
+        history
+            .iter()
+            .position(|totp| totp.drift_at(otp, time, window).is_some())
+    }
+
+    /**
+    Searches `search_window` steps on both sides of the current time for `otp`, reporting
+    the drift of the submitted code or the boundaries that were searched.
+
+    This is a support tool to investigate codes that never validate (e.g. a device clock
+    far off), not a replacement for `check`: a wide search window must never be used
+    to accept codes.
+
+    # Example
+
+    ```
+    use ootp::totp::{CreateOption, DiagnoseResult, Totp};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let otp = totp.make_drift(10);
+    assert!(matches!(
+        totp.diagnose(&otp, 20),
+        DiagnoseResult::Matched { drift: 9..=11 }
+    ));
+    ```
+    */
+    pub fn diagnose(&self, otp: &str, search_window: u64) -> DiagnoseResult {
+        let time = self.clock.now_secs();
+        match self.drift_at(otp, time, search_window) {
+            Some(drift) => DiagnoseResult::Matched { drift },
+            None => {
+                let counter = self.step_at(time);
+                DiagnoseResult::NotFound {
+                    first_counter: counter.saturating_sub(search_window),
+                    last_counter: counter.saturating_add(search_window),
+                }
+            }
+        }
+    }
+
+    /**
+    Verifies `otp` within `window` steps of the current time, reporting why it was rejected.
+
+    A code which would be valid with a different number of digits is reported as
+    `VerifyResult::DigitCountMismatch`, which usually means the authenticator app is
+    configured with the wrong digit count. A code matching a future step is accepted as
+    `VerifyResult::FutureMatch`, warning that the submitter's clock is fast.
+
+    # Example
+
+    ```
+    use ootp::totp::{CreateOption, Totp, VerifyResult};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let otp = totp.make();
+    assert!(totp.verify_detailed(&otp, 1).is_accepted());
+    assert_eq!(
+        totp.verify_detailed(&totp.make_drift(1), 1),
+        VerifyResult::FutureMatch { steps: 1 }
+    );
+    ```
+    */
+    pub fn verify_detailed(&self, otp: &str, window: u64) -> VerifyResult {
+        let time = self.clock.now_secs();
+        let otp = self.normalize_code(otp);
+        match self.drift_with_digits(&otp, time, window, self.digits) {
+            Some(drift) if drift > 0 => {
+                return VerifyResult::FutureMatch {
+                    steps: drift as u64,
+                }
+            }
+            Some(drift) => return VerifyResult::Valid { drift },
+            None => {}
+        }
+        let submitted_len = otp.len();
+        let expected_len = self.digits as usize;
+        if submitted_len != expected_len
+            && otp.bytes().all(|b| b.is_ascii_digit())
+            && self
+                .drift_with_digits(&otp, time, window, submitted_len as u32)
+                .is_some()
+        {
+            return VerifyResult::DigitCountMismatch {
+                submitted_len,
+                expected_len,
+            };
+        }
+        VerifyResult::Invalid
+    }
+
+    /**
+    Verifies `otp` at the step of `claimed_time`, the time the code was submitted at
+    (e.g. when it was delivered through a queue), rejecting it when `claimed_time`
+    is more than `max_age_secs` seconds in the past or is in the future.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(!totp.verify_at_with_freshness(&totp.make_time(59), 59, 300));
+    ```
+    */
+    pub fn verify_at_with_freshness(
+        &self,
+        otp: &str,
+        claimed_time: u64,
+        max_age_secs: u64,
+    ) -> bool {
+        self.verify_freshness(otp, claimed_time, max_age_secs, 0)
+            .is_ok()
+    }
+
+    /**
+    Like `verify_at_with_freshness`, tolerating a `claimed_time` up to `allowed_skew`
+    seconds in the future, e.g. from a producer with a clock slightly ahead, and telling
+    why a code is rejected.
+
+    # Example
+
+    ```
+    use ootp::totp::{FreshnessError, Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(matches!(
+        totp.verify_freshness(&totp.make_time(59), 59, 300, 0),
+        Err(FreshnessError::Stale { .. })
+    ));
+    ```
+    */
+    pub fn verify_freshness(
+        &self,
+        otp: &str,
+        claimed_time: u64,
+        max_age_secs: u64,
+        allowed_skew: u64,
+    ) -> Result<(), FreshnessError> {
+        let now = self.clock.now_secs();
+        if claimed_time > now {
+            let ahead = claimed_time - now;
+            if ahead > allowed_skew {
+                return Err(FreshnessError::FromFuture { ahead });
+            }
+        } else if now - claimed_time > max_age_secs {
+            return Err(FreshnessError::Stale {
+                age: now - claimed_time,
+            });
+        }
+        match self.drift_at(otp, claimed_time, 0) {
+            Some(_) => Ok(()),
+            None => Err(FreshnessError::Invalid),
+        }
+    }
+
+    /**
+    Verifies the one-time password ending `combined`, a password immediately followed by
+    the code as typed in single-field login forms, and returns the password on success.
+
+    The code is the trailing `digits` characters, verified like `check(code, Some(window))`:
+    within `window` steps either side of the current one.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let combined = format!("hunter2{}", totp.make());
+    assert_eq!(totp.check_suffix(&combined, 1), Some("hunter2"));
+    assert_eq!(totp.check_suffix("hunter2", 1), None);
+    ```
+    */
+    pub fn check_suffix<'b>(&self, combined: &'b str, window: u64) -> Option<&'b str> {
+        let split = combined.len().checked_sub(self.digits as usize)?;
+        let radix = self.radix.clamp(2, 36);
+        let code = combined.get(split..)?;
+        if code.chars().all(|c| c.is_digit(radix)) && self.check(code, Some(window)) {
+            Some(&combined[..split])
+        } else {
+            None
+        }
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password, computed with `algorithm` instead
+    of the configured one, is valid within `window` steps of the current time,
+    e.g. while migrating to another algorithm.
+
+    # Example
+
+    ```
+    use ootp::hmacsha::ShaTypes;
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let sha256 = Totp::secret(secret.clone(), CreateOption::Algorithm(&ShaTypes::Sha2_256));
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.check_with_algorithm(&sha256.make(), &ShaTypes::Sha2_256, 1));
+    ```
+    */
+    pub fn check_with_algorithm(&self, otp: &str, algorithm: &ShaTypes, window: u64) -> bool {
+        let otp = self.normalize_code(otp);
+        self.find_drift(self.clock.now_secs(), window, |counter| {
+            let code = self
+                .hotp
+                .make_radix(counter, self.digits, self.radix, algorithm);
+            constant_time_eq(code.as_bytes(), otp.as_bytes())
+        })
+        .is_some()
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is valid within the clock skew
+    tolerance of `policy`.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption, SkewPolicy};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let policy = SkewPolicy { back_steps: 1, forward_steps: 0, reject_future: true };
+    assert!(totp.check_policy(&totp.make_drift(-1), &policy));
+    assert!(!totp.check_policy(&totp.make_drift(1), &policy));
+    ```
+    */
+    pub fn check_policy(&self, otp: &str, policy: &SkewPolicy) -> bool {
+        let otp = self.normalize_code(otp);
+        let forward = if policy.reject_future {
+            0
+        } else {
+            policy.forward_steps
+        };
+        self.find_drift_between(
+            self.clock.now_secs(),
+            policy.back_steps,
+            forward,
+            |counter| {
+                constant_time_eq(
+                    self.code_at(counter, self.digits).as_bytes(),
+                    otp.as_bytes(),
+                )
+            },
+        )
+        .is_some()
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is valid for the current step or
+    one of the `ceil(estimated_rtt_secs / period)` previous ones, compensating for the
+    network delay of a code generated before it arrived. Future steps are never accepted.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.check_with_rtt(&totp.make_drift(-1), 31));
+    ```
+    */
+    pub fn check_with_rtt(&self, otp: &str, estimated_rtt_secs: u64) -> bool {
+        let otp = self.normalize_code(otp);
+        let back = estimated_rtt_secs.div_ceil(self.period());
+        self.find_drift_between(self.clock.now_secs(), back, 0, |counter| {
+            constant_time_eq(
+                self.code_at(counter, self.digits).as_bytes(),
+                otp.as_bytes(),
+            )
+        })
+        .is_some()
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is valid for the current step or
+    expired at most `grace_secs` seconds ago, e.g. for requests initiated just before the
+    code expired but processed just after. A zero `grace_secs` accepts the current step
+    only, and future steps are never accepted.
+
+    Unlike `check_with_rtt`, the grace is exact to the second rather than rounded up
+    to whole steps.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.check_with_grace(&totp.make(), 0));
+    ```
+    */
+    pub fn check_with_grace(&self, otp: &str, grace_secs: u64) -> bool {
+        let otp = self.normalize_code(otp);
+        let time = self.clock.now_secs();
+        // A step expiring at `time - grace_secs` was last valid the second before.
+        let oldest = match grace_secs {
+            0 => time,
+            grace_secs => time.saturating_sub(grace_secs).saturating_sub(1),
+        };
+        let back = self.step_at(time) - self.step_at(oldest);
+        self.find_drift_between(time, back, 0, |counter| {
+            constant_time_eq(
+                self.code_at(counter, self.digits).as_bytes(),
+                otp.as_bytes(),
+            )
+        })
+        .is_some()
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is valid within `window` steps of
+    the current time and accepted by `policy`, which receives the `VerifyContext` of the
+    match and can veto it, e.g. for time-of-day restrictions.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(totp.check_with_policy_fn(&totp.make(), 1, |context| context.drift == 0));
+    assert!(!totp.check_with_policy_fn(&totp.make(), 1, |_| false));
+    ```
+    */
+    pub fn check_with_policy_fn(
+        &self,
+        otp: &str,
+        window: u64,
+        policy: impl Fn(VerifyContext) -> bool,
+    ) -> bool {
+        let now = self.clock.now_secs();
+        let drift = match self.drift_at(otp, now, window) {
+            Some(drift) => drift,
+            None => return false,
+        };
+        let counter = self.step_at(now).saturating_add_signed(drift);
+        policy(VerifyContext {
+            drift,
+            counter,
+            matched_time: counter.saturating_mul(self.period()),
+            now,
+        })
+    }
+
+    /**
+    Returns the index of the first of `candidates` equal to `otp` when `otp` is the current
+    code, telling which of several stored codes a user entered, or `None` otherwise. `otp`
+    is normalized like in `check`, and the current code and every candidate are compared
+    in constant time, even after a match.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let current = totp.make();
+    assert_eq!(totp.match_index(&current, &["backup", &current]), Some(1));
+    ```
+    */
+    pub fn match_index(&self, otp: &str, candidates: &[&str]) -> Option<usize> {
+        let otp = self.normalize_code(otp);
+        if otp.is_empty() {
+            return None;
+        }
+        let current = constant_time_eq(self.make().as_bytes(), otp.as_bytes());
+        let first = candidates
+            .iter()
+            .enumerate()
+            .fold(None, |first, (index, candidate)| {
+                let equal = constant_time_eq(candidate.as_bytes(), otp.as_bytes());
+                first.or(if equal { Some(index) } else { None })
+            });
+        if current {
+            first
+        } else {
+            None
+        }
+    }
+
+    /**
+    Returns a boolean indicating if both `code_a` and `code_b` are valid one-time passwords
+    of this secret within `window` steps of the current time, e.g. to detect shared codes.
+
+    Both codes are always checked, in constant time, against a single clock sample.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let otp = totp.make();
+    assert!(totp.same_window(&otp, &otp, 1));
+    ```
+    */
+    pub fn same_window(&self, code_a: &str, code_b: &str, window: u64) -> bool {
+        let time = self.clock.now_secs();
+        let valid_a = self.drift_at(code_a, time, window).is_some();
+        let valid_b = self.drift_at(code_b, time, window).is_some();
+        valid_a & valid_b
+    }
+
+    /**
+    Returns a boolean indicating if any one-time password within `window` steps of the
+    current time starts with `partial`, e.g. to hint at a correct entry while it is typed.
+    An empty `partial`, or one of only whitespace, matches nothing.
+
+    **This does not authenticate anything**: a short prefix matches by chance and is
+    trivially guessed. It is meant for UX hints only, a complete code must still be
+    verified with `check` or a similar method before granting access.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let otp = totp.make();
+    assert!(totp.could_match_prefix(&otp[..3], 1));
+    ```
+    */
+    pub fn could_match_prefix(&self, partial: &str, window: u64) -> bool {
+        if partial.len() > MAX_OTP_INPUT_LEN {
+            return false;
+        }
+        let partial = normalize(partial);
+        !partial.is_empty()
+            && partial.len() <= self.digits as usize
+            && self
+                .find_drift(self.clock.now_secs(), window, |counter| {
+                    self.code_at(counter, self.digits).starts_with(&partial)
+                })
+                .is_some()
+    }
+
+    /**
+    Verifies `otp` within `window` steps of `at_time`, a Unix time in seconds supplied by
+    the caller, e.g. an authoritative time from a coordinator, and returns its drift in steps.
+    The `Totp`'s clock is not read.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.check_at(&totp.make_time(59), 89, 1), Some(-1));
+    ```
+    */
+    pub fn check_at(&self, otp: &str, at_time: u64, window: u64) -> Option<i64> {
+        self.drift_at(otp, at_time, window)
+    }
+
+    /**
+    Like `check(otp, window)` with `window` in steps, also returning the start of the matched
+    window as a UTC RFC 3339 timestamp, e.g. for audit logs.
+
+    Returns `None` if the code does not match, or if the window start is outside the years
+    supported by RFC 3339.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let (drift, start) = totp.verify_with_timestamp(&totp.make(), 1).unwrap();
+    assert!(start.ends_with('Z'));
+    ```
+    */
+    #[cfg(feature = "time")]
+    pub fn verify_with_timestamp(&self, otp: &str, window: u64) -> Option<(i64, String)> {
+        use std::convert::TryFrom;
+
+        let time = self.clock.now_secs();
+        let drift = self.drift_at(otp, time, window)?;
+        let start = self
+            .step_at(time)
+            .checked_add_signed(drift)?
+            .checked_mul(self.period())?;
+        let start = time::OffsetDateTime::from_unix_timestamp(i64::try_from(start).ok()?).ok()?;
+        let start = start
+            .format(&time::format_description::well_known::Rfc3339)
+            .ok()?;
+        Some((drift, start))
+    }
+
+    /**
+    Returns a boolean indicating if the one-time password is the one of the current step,
+    without any tolerance for clock skew.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert!(!totp.verify_strict(&totp.make_drift(-1)));
+    ```
+    */
+    pub fn verify_strict(&self, otp: &str) -> bool {
+        self.drift_at(otp, self.clock.now_secs(), 0).is_some()
+    }
+
+    /**
+    Returns the number of seconds a code is accepted for by a verification with `window`
+    steps of tolerance, `(2 * window + 1) * period`, to audit the exposure of a configuration.
+    Saturates at `u64::MAX`.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.effective_acceptance_secs(2), 150);
+    ```
+    */
+    pub fn effective_acceptance_secs(&self, window: u64) -> u64 {
+        window
+            .saturating_mul(2)
+            .saturating_add(1)
+            .saturating_mul(self.period())
+    }
+
+    /**
+    Returns the average number of random guesses needed to hit the code of a single step,
+    half the number of possible codes: `10^digits / 2`, or `radix^digits / 2` with another
+    radix. E.g. about 500,000 guesses for 6 digits, for security dashboards.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.expected_attempts_to_break(), 500_000.0);
+    ```
+    */
+    pub fn expected_attempts_to_break(&self) -> f64 {
+        f64::from(self.radix.clamp(2, 36)).powf(f64::from(self.digits)) / 2.0
+    }
+
+    /**
+    Returns, in ascending order, the counters a verification with `window` steps of
+    tolerance considers at the current time, clamped to the valid counter range.
+
+    This is informational, e.g. for audit logs, and does not affect verification.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    assert_eq!(totp.window_counters(2).len(), 5);
+    ```
+    */
+    pub fn window_counters(&self, window: u64) -> Vec<u64> {
+        self.window_counters_at(self.clock.now_secs(), window)
+    }
+
+    fn window_counters_at(&self, time: u64, window: u64) -> Vec<u64> {
+        let counter = self.step_at(time);
+        (counter.saturating_sub(window)..=counter.saturating_add(window)).collect()
+    }
+
+    /**
+    Returns the codes a verification with `window` steps of tolerance accepts at `time`,
+    in ascending counter order, e.g. for server-side audits.
+
+    The codes are wrapped in `ValidCodes`, whose `Debug` and `Display` outputs are redacted
+    to prevent accidental logging: call `reveal` to read them.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let codes = totp.valid_codes_at(1_000, 1);
+    assert_eq!(codes.reveal().len(), 3);
+    assert!(codes.reveal().contains(&totp.make_time(1_000)));
+    ```
+    */
+    pub fn valid_codes_at(&self, time: u64, window: u64) -> ValidCodes {
+        ValidCodes(
+            self.window_counters_at(time, window)
+                .into_iter()
+                .map(|counter| self.code_at(counter, self.digits))
+                .collect(),
+        )
+    }
+
+    /**
+    Verifies `otp` within `window` steps of the current time with each of the `algorithms`,
+    in order, returning the first algorithm producing it along with its drift.
+
+    This lets a server learn which algorithm an authenticator app actually uses
+    and persist it for the subsequent verifications.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+    use ootp::hmacsha::ShaTypes;
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let app = Totp::secret(secret.clone(), CreateOption::Algorithm(&ShaTypes::Sha2_512));
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let algorithms = [&ShaTypes::Sha1, &ShaTypes::Sha2_256, &ShaTypes::Sha2_512];
+    let learned = totp.verify_learn(&app.make(), &algorithms, 1);
+    assert!(matches!(learned, Some((ShaTypes::Sha2_512, _))));
+    ```
+    */
+    pub fn verify_learn<'b>(
+        &self,
+        otp: &str,
+        algorithms: &[&'b ShaTypes],
+        window: u64,
+    ) -> Option<(&'b ShaTypes, i64)> {
+        let time = self.clock.now_secs();
+        let otp = self.normalize_code(otp);
+        algorithms.iter().find_map(|&algorithm| {
+            self.find_drift(time, window, |counter| {
+                let code = self
+                    .hotp
+                    .make_radix(counter, self.digits, self.radix, algorithm);
+                constant_time_eq(code.as_bytes(), otp.as_bytes())
+            })
+            .map(|drift| (algorithm, drift))
+        })
+    }
+
+    /**
+    Verifies `otp` within `window` steps of the current time with each of the `periods`,
+    in order, returning the first period producing it along with its drift.
+
+    Like `verify_learn` for algorithms, this lets a server learn which period an
+    authenticator app actually uses and persist it for the subsequent verifications.
+    Zero periods are skipped.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let app = Totp::secret(secret.clone(), CreateOption::Period(60));
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let learned = totp.verify_learn_period(&app.make(), &[30, 60], 1);
+    assert_eq!(learned.map(|(period, _)| period), Some(60));
+    ```
+    */
+    pub fn verify_learn_period(
+        &self,
+        otp: &str,
+        periods: &[u64],
+        window: u64,
+    ) -> Option<(u64, i64)> {
+        let time = self.clock.now_secs();
+        let otp = self.normalize_code(otp);
+        if otp.is_empty() {
+            return None;
+        }
+        periods
+            .iter()
+            .filter(|&&period| period != 0)
+            .find_map(|&period| {
+                let counter = (time / period).saturating_add_signed(self.fixed_drift);
+                find_drift_around(counter, window, window, |counter| {
+                    constant_time_eq(
+                        self.code_at(counter, self.digits).as_bytes(),
+                        otp.as_bytes(),
+                    )
+                })
+                .map(|drift| (period, drift))
+            })
+    }
+
+    /**
+    Verifies `otp` within `window` steps of the current time and returns the generated code
+    it matched, i.e. the normalized form of `otp`.
+
+    The matched code is a valid credential: only log it server side, with care.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    let code = totp.make();
+    let spaced = format!("{} {}", &code[..3], &code[3..]);
+    assert_eq!(totp.verify_matched_code(&spaced, 1), Some(code));
+    ```
+    */
+    pub fn verify_matched_code(&self, otp: &str, window: u64) -> Option<String> {
+        let time = self.clock.now_secs();
+        let otp = self.normalize_code(otp);
+        self.drift_with_digits(&otp, time, window, self.digits)
+            .map(|drift| self.code_at(self.step_at(time).saturating_add_signed(drift), self.digits))
+    }
+
+    /**
+    Generates a code at a fixed counter and verifies it against itself, so a misconfigured
+    algorithm or HMAC backend fails at startup rather than at the first authentication.
+
+    # Example
+
+    ```
+    use ootp::totp::{Totp, CreateOption};
+
+    let secret = "A strong shared secret".as_bytes().to_vec();
+    let totp = Totp::secret(secret, CreateOption::Default);
+    totp.self_test().expect("TOTP self test");
+    ```
+    */
+    pub fn self_test(&self) -> Result<(), SelfTestError> {
+        const COUNTER: u64 = 1;
+        let code = self.code_at(COUNTER, self.digits);
+        if code != self.code_at(COUNTER, self.digits) {
+            return Err(SelfTestError::Inconsistent);
+        }
+        let radix = self.radix.clamp(2, 36);
+        if code.len() != self.digits as usize || !code.chars().all(|c| c.is_digit(radix)) {
+            return Err(SelfTestError::MalformedCode);
+        }
+        let check = CheckOption::Full {
+            counter: COUNTER,
+            breadth: 0,
+            algorithm: self.algorithm,
+        };
+        let verified = if radix == DEFAULT_RADIX {
+            self.hotp.check(&code, check)
+        } else {
+            let encoder = |value| format_code_radix(value, self.digits, radix);
+            self.hotp.check_with(&code, encoder, check)
+        };
+        if !verified {
+            return Err(SelfTestError::Rejected);
+        }
+        Ok(())
+    }
+
+    /// Returns the one-time password of the step `counter`.
+    fn code_at(&self, counter: u64, digits: u32) -> String {
+        self.hotp
+            .make_radix(counter, digits, self.radix, self.algorithm)
+    }
+
+    /// Returns the drift, in steps, of the code matching `otp` within `window` steps
+    /// of `time`, trying the closest steps first.
+    fn drift_at(&self, otp: &str, time: u64, window: u64) -> Option<i64> {
+        self.drift_with_digits(&self.normalize_code(otp), time, window, self.digits)
+    }
+
+    /// Returns a boolean indicating if the normalized `otp` matches a counter within `window`
+    /// steps of `time`, comparing every counter of the window without short-circuiting.
+    fn matches_window_uniformly(&self, otp: &str, time: u64, window: u64) -> bool {
+        if otp.is_empty() {
+            return false;
+        }
+        let counter = self.step_at(time);
+        let first = counter.saturating_sub(window);
+        let last = counter.saturating_add(window);
+        (first..=last).fold(false, |matched, counter| {
+            matched
+                | constant_time_eq(
+                    self.code_at(counter, self.digits).as_bytes(),
+                    otp.as_bytes(),
+                )
+        })
+    }
+
+    /// Normalizes `otp` like `normalize`, restoring the leading zeros stripped by some
+    /// clients when `allow_stripped_leading_zeros` is set.
+    fn normalize_code(&self, otp: &str) -> String {
+        let otp = normalize(otp);
+        let digits = self.digits as usize;
+        if self.allow_stripped_leading_zeros
+            && !otp.is_empty()
+            && otp.len() < digits
+            && otp.bytes().all(|b| b.is_ascii_digit())
+        {
+            format!("{:0>width$}", otp, width = digits)
+        } else {
+            otp
+        }
+    }
+
+    /// Like `drift_at` for an already normalized `otp` of `digits` digits.
+    fn drift_with_digits(&self, otp: &str, time: u64, window: u64, digits: u32) -> Option<i64> {
+        if otp.is_empty() {
+            return None;
+        }
+        self.find_drift(time, window, |counter| {
+            constant_time_eq(self.code_at(counter, digits).as_bytes(), otp.as_bytes())
+        })
+    }
+
+    /// Returns the drift, in steps, of the first counter within `window` steps of `time`
+    /// accepted by `matches`, trying the closest steps first.
+    fn find_drift(&self, time: u64, window: u64, matches: impl Fn(u64) -> bool) -> Option<i64> {
+        self.find_drift_between(time, window, window, matches)
+    }
+
+    /// Like `find_drift` with `back` steps of tolerance in the past and `forward` steps
+    /// in the future.
+    fn find_drift_between(
+        &self,
+        time: u64,
+        back: u64,
+        forward: u64,
+        matches: impl Fn(u64) -> bool,
+    ) -> Option<i64> {
+        find_drift_around(self.step_at(time), back, forward, matches)
+    }
+
+    /// Returns the counter of the code matching `otp` within `window` steps of the current
+    /// time and strictly above `floor`, trying the closest steps first.
+    pub(crate) fn matched_counter_above(
+        &self,
+        otp: &str,
+        window: u64,
+        floor: Option<u64>,
+    ) -> Option<u64> {
+        self.matched_counter_where(otp, window, |counter| {
+            floor.is_none_or(|floor| counter > floor)
+        })
+    }
+
+    /// Returns the counter of the code matching `otp` within `window` steps of the current
+    /// time among the counters satisfying `accept`, trying the closest steps first.
+    pub(crate) fn matched_counter_where(
+        &self,
+        otp: &str,
+        window: u64,
+        accept: impl Fn(u64) -> bool,
+    ) -> Option<u64> {
+        let time = self.clock.now_secs();
+        let otp = self.normalize_code(otp);
+        if otp.is_empty() {
+            return None;
+        }
+        let drift = self.find_drift(time, window, |counter| {
+            accept(counter)
+                && constant_time_eq(
+                    self.code_at(counter, self.digits).as_bytes(),
+                    otp.as_bytes(),
+                )
+        })?;
+        Some(self.step_at(time).saturating_add_signed(drift))
+    }
+
+    fn remaining_at(&self, time: u64) -> u64 {
+        self.period() - time % self.period()
+    }
+
+    fn seconds_until_counter_at(&self, counter: u64, time: u64) -> Option<u64> {
+        let current = self.step_at(time);
+        if counter < current {
+            return None;
+        }
+        if counter == current {
+            return Some(0);
+        }
+        let start = (i128::from(counter) - i128::from(self.fixed_drift))
+            .saturating_mul(i128::from(self.period()));
+        Some((start - i128::from(time)).clamp(0, i128::from(u64::MAX)) as u64)
+    }
+}
+
+/// The 5 rows of the 3 columns wide block glyph of each decimal digit.
+const DIGIT_GLYPHS: [[&str; 5]; 10] = [
+    ["###", "# #", "# #", "# #", "###"],
+    ["  #", "  #", "  #", "  #", "  #"],
+    ["###", "  #", "###", "#  ", "###"],
+    ["###", "  #", "###", "  #", "###"],
+    ["# #", "# #", "###", "  #", "  #"],
+    ["###", "#  ", "###", "  #", "###"],
+    ["###", "#  ", "###", "# #", "###"],
+    ["###", "  #", "  #", "  #", "  #"],
+    ["###", "# #", "###", "# #", "###"],
+    ["###", "# #", "###", "  #", "###"],
+];
+
+/// Renders `code` with `DIGIT_GLYPHS`, see `Totp::make_ascii_art`.
+fn ascii_art(code: &str) -> String {
+    (0..5)
+        .map(|row| {
+            code.chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(digit) => DIGIT_GLYPHS[digit as usize][row].to_string(),
+                    None if row == 2 => format!(" {} ", c),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the drift, in steps, of the first counter within `back` steps before and
+/// `forward` steps after `counter` accepted by `matches`, trying the closest steps first.
+fn find_drift_around(
+    counter: u64,
+    back: u64,
+    forward: u64,
+    matches: impl Fn(u64) -> bool,
+) -> Option<i64> {
+    (0..=back.max(forward)).find_map(|step| {
+        if step <= forward && counter.checked_add(step).is_some_and(&matches) {
+            Some(step as i64)
+        } else if step != 0 && step <= back && counter.checked_sub(step).is_some_and(&matches) {
+            Some(-(step as i64))
+        } else {
+            None
+        }
+    })
+}
+
+/**
+Verifies each `(totp, otp)` pair of `entries` within `window` steps of the current time,
+returning one boolean per entry, in order.
+
+With the `rayon` feature, the entries are verified in parallel.
+
+Every counter of the window of every entry is compared, without short-circuiting,
+so that the verification time does not depend on which entries, or counters, match.
+
+# Example
+
+```
+use ootp::totp::{verify_batch, Totp, CreateOption};
+
+let totp = Totp::secret("A strong shared secret".as_bytes().to_vec(), CreateOption::Default);
+let otp = totp.make();
+let entries = [(totp, otp), (Totp::secret(vec![1, 2, 3], CreateOption::Default), "".to_string())];
+assert_eq!(verify_batch(&entries, 1), [true, false]);
+```
+*/
+pub fn verify_batch(entries: &[(Totp, String)], window: u64) -> Vec<bool> {
+    let verify = |(totp, otp): &(Totp, String)| {
+        totp.matches_window_uniformly(&totp.normalize_code(otp), totp.clock.now_secs(), window)
+    };
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        entries.par_iter().map(verify).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        entries.iter().map(verify).collect()
+    }
+}
+
+/**
+Verifies a submitted code against many secrets sharing the same `OtpConfig`,
+e.g. to find which user a code belongs to.
+
+To avoid leaking through timing which secret matched, if any, every counter of the window
+of every secret is always compared and the matching index is selected in constant time.
+
+# Example
+
+```
+use ootp::totp::{MultiSecretVerifier, OtpConfig, Totp};
+
+let secrets = vec![b"first secret".to_vec(), b"second secret".to_vec()];
+let otp = Totp::from_config(secrets[1].clone(), OtpConfig::default()).make();
+let verifier = MultiSecretVerifier::new(OtpConfig::default(), secrets);
+assert_eq!(verifier.verify(&otp, 1), Some(1));
+```
+*/
+pub struct MultiSecretVerifier<'a> {
+    pub config: OtpConfig<'a>,
+    pub totps: Vec<Totp<'a>>,
+}
+
+impl<'a> MultiSecretVerifier<'a> {
+    /// Creates a verifier of `secrets`, all configured with `config`.
+    pub fn new(config: OtpConfig<'a>, secrets: Vec<Vec<u8>>) -> Self {
+        let totps = secrets
+            .into_iter()
+            .map(|secret| Totp::from_config(secret, config))
+            .collect();
+        Self { config, totps }
+    }
+
+    /// Replaces the `TimeSource` used to read the current time.
+    pub fn with_clock(mut self, clock: &'a dyn TimeSource) -> Self {
+        for totp in self.totps.iter_mut() {
+            totp.clock = clock;
+        }
+        self
+    }
+
+    /// Returns the index of the first secret `otp` is valid for within `window` steps
+    /// of the current time, without short-circuiting on a match.
+    pub fn verify(&self, otp: &str, window: u64) -> Option<usize> {
+        let otp = normalize(otp);
+        let (mut index, mut found) = (0_usize, 0_usize);
+        for (i, totp) in self.totps.iter().enumerate() {
+            let time = totp.clock.now_secs();
+            let matched = totp.matches_window_uniformly(&otp, time, window) as usize;
+            // All ones for the first match only, all zeros otherwise.
+            let select = (matched & !found).wrapping_neg();
+            index = (i & select) | (index & !select);
+            found |= matched;
+        }
+        if found == 1 {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+/**
+Tries every combination of the common algorithms (SHA1, SHA256, SHA512), digit counts
+(6, 7, 8) and periods (30, 60) and returns each configuration for which `otp` is valid
+within `window` steps of `at_time`, along with its drift, e.g. to debug the interoperability
+with an authenticator app. Empty if the code does not come from `secret` at all.
+
+# Example
+
+```
+use ootp::totp::{diagnose_any, CreateOption, Totp};
+
+let secret = "A strong shared secret".as_bytes().to_vec();
+let otp = Totp::secret(secret.clone(), CreateOption::Digits(8)).make_time(1_000);
+let matches = diagnose_any(&secret, &otp, 1_000, 0);
+assert!(matches.iter().any(|(config, drift)| config.digits == 8 && *drift == 0));
+```
+*/
+pub fn diagnose_any(
+    secret: &[u8],
+    otp: &str,
+    at_time: u64,
+    window: u64,
+) -> Vec<(OtpConfig<'static>, i64)> {
+    const ALGORITHMS: [&ShaTypes; 3] = [&ShaTypes::Sha1, &ShaTypes::Sha2_256, &ShaTypes::Sha2_512];
+    let mut matches = Vec::new();
+    for &algorithm in ALGORITHMS.iter() {
+        for &digits in [6, 7, 8].iter() {
+            for &period in [30, 60].iter() {
+                let config = OtpConfig {
+                    digits,
+                    period,
+                    algorithm,
+                    ..OtpConfig::default()
+                };
+                let totp = Totp::from_config(secret.to_vec(), config);
+                if let Some(drift) = totp.check_at(otp, at_time, window) {
+                    matches.push((config, drift));
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// The identifier of a secret in a multi-tenant deployment, see `parse_keyed_code`.
+pub type KeyId = u32;
+
+/**
+Parses a code prefixed with the identifier of its secret, `"<id>;<code>"` or
+`"kid=<id>;<code>"`, returning the identifier and the code.
+
+# Example
+
+```
+use ootp::totp::parse_keyed_code;
+
+assert_eq!(parse_keyed_code("kid=3;123456"), Some((3, "123456")));
+assert_eq!(parse_keyed_code("123456"), None);
+```
+*/
+pub fn parse_keyed_code(input: &str) -> Option<(KeyId, &str)> {
+    let (id, code) = input.split_once(';')?;
+    let id = id.trim();
+    let id = id.strip_prefix("kid=").unwrap_or(id);
+    Some((id.parse().ok()?, code))
+}
+
+/**
+A verifier of codes prefixed with the identifier of their secret, see `parse_keyed_code`,
+routing each code to the `Totp` registered under its identifier.
+
+# Example
+
+```
+use ootp::totp::{CreateOption, KeyedVerifier, Totp};
+use std::collections::HashMap;
+
+let mut totps = HashMap::new();
+totps.insert(3, Totp::secret("A strong shared secret".as_bytes().to_vec(), CreateOption::Default));
+let otp = totps[&3].make();
+let verifier = KeyedVerifier { totps, window: 1 };
+assert_eq!(verifier.verify(&format!("3;{}", otp)), Some(3));
+assert_eq!(verifier.verify(&format!("4;{}", otp)), None);
+```
+*/
+pub struct KeyedVerifier<'a> {
+    pub totps: HashMap<KeyId, Totp<'a>>,
+    pub window: u64,
+}
+
+impl KeyedVerifier<'_> {
+    /// Returns the identifier of the secret of `input` if its code is valid within
+    /// `window` steps of the current time, or `None` if it is invalid, malformed or
+    /// its identifier is unknown.
+    pub fn verify(&self, input: &str) -> Option<KeyId> {
+        let (id, otp) = parse_keyed_code(input)?;
+        let totp = self.totps.get(&id)?;
+        totp.drift_at(otp, totp.clock.now_secs(), self.window)
+            .map(|_| id)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{
+        ascii_art, diagnose_any, parse_keyed_code, verify_batch, CreateOption, DiagnoseResult,
+        FreshnessError, KeyedVerifier, MultiSecretVerifier, OtpConfig, SelfTestError, SkewPolicy,
+        Totp, VerifyContext, VerifyResult, DIGIT_GLYPHS,
+    };
+    use crate::clock::TimeSource;
+    use crate::constants::{self, DEFAULT_DIGITS, MAX_OTP_INPUT_LEN};
+    use crate::encoding::DecodeError;
+    use crate::hotp::{format_code_radix, CheckOption};
+    use hmacsha::ShaTypes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    pub(crate) struct FixedClock(pub(crate) u64);
+
+    impl TimeSource for FixedClock {
+        fn now_secs(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default);
+        let code = totp.make();
+        assert_eq!(code.len(), DEFAULT_DIGITS as usize);
+    }
+
+    /// Taken from [RFC 6238](https://datatracker.ietf.org/doc/html/rfc6238#appendix-B)
+    #[test]
+    fn make_test_correcteness() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Digits(8));
+        let code = totp.make_time(59);
+        assert_eq!(code, "94287082");
+        let code = totp.make_time(1_111_111_109);
+        assert_eq!(code, "07081804");
+        let code = totp.make_time(1_111_111_111);
+        assert_eq!(code, "14050471");
+        let code = totp.make_time(1_234_567_890);
+        assert_eq!(code, "89005924");
+        let code = totp.make_time(2_000_000_000);
+        assert_eq!(code, "69279037");
+        let code = totp.make_time(20_000_000_000);
+        assert_eq!(code, "65353130");
+    }
+
+    /// Taken from [RFC 6238](https://datatracker.ietf.org/doc/html/rfc6238#appendix-B)
+    /// Errata for [RFC 6238]](https://www.rfc-editor.org/errata_search.php?rfc=6238&rec_status=0)
+    #[test]
+    fn make_test_correcteness_sha256() {
+        let secret = "12345678901234567890123456789012".as_bytes().to_vec();
+        let totp = Totp::secret(
+            secret,
+            CreateOption::Full {
+                digits: 8,
+                period: constants::DEFAULT_PERIOD,
+                algorithm: &hmacsha::ShaTypes::Sha2_256,
+            },
+        );
+        let code = totp.make_time(59);
+        assert_eq!(code, "46119246");
+        let code = totp.make_time(1_111_111_109);
+        assert_eq!(code, "68084774");
+        let code = totp.make_time(1_111_111_111);
+        assert_eq!(code, "67062674");
+        let code = totp.make_time(1_234_567_890);
+        assert_eq!(code, "91819424");
+        let code = totp.make_time(2_000_000_000);
+        assert_eq!(code, "90698825");
+        let code = totp.make_time(20_000_000_000);
+        assert_eq!(code, "77737706");
+    }
+
+    #[test]
+    fn check_test() {
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default);
+        let code = totp.make();
+        assert!(totp.check(code.as_str(), None))
+    }
+
+    #[test]
+    fn adversarial_period_test() {
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Period(0));
+        assert_eq!(totp.remaining(), 1);
+        assert!(totp.seconds_until_counter(u64::MAX).is_some());
+        assert_eq!(totp.make().len(), DEFAULT_DIGITS as usize);
+        assert!(!totp.check("", Some(2)));
+        assert_eq!(totp.make_drift(i64::MIN), totp.make_time(0));
+        assert_eq!(totp.make_drift(i64::MAX).len(), DEFAULT_DIGITS as usize);
+    }
+
+    #[test]
+    fn seconds_until_next_counter_test() {
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default);
+        let time = 1_111_111_109;
+        let next = time / totp.period + 1;
+        assert_eq!(
+            totp.seconds_until_counter_at(next, time),
+            Some(totp.remaining_at(time))
+        );
+        assert_eq!(totp.seconds_until_counter_at(next, time), Some(1));
+        assert_eq!(totp.seconds_until_counter_at(next - 1, time), Some(0));
+    }
+
+    #[test]
+    fn verify_future_match_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let result = totp.verify_detailed(&totp.make_drift(1), 2);
+        assert_eq!(result, VerifyResult::FutureMatch { steps: 1 });
+        assert!(result.is_accepted());
+        assert_eq!(
+            totp.verify_detailed(&totp.make_drift(2), 2),
+            VerifyResult::FutureMatch { steps: 2 }
+        );
+        assert_eq!(
+            totp.verify_detailed(&totp.make_drift(-1), 2),
+            VerifyResult::Valid { drift: -1 }
+        );
+        assert!(!totp.verify_detailed(&totp.make_drift(3), 2).is_accepted());
+        assert!(!VerifyResult::Invalid.is_accepted());
+    }
+
+    #[test]
+    fn current_valid_code_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert_eq!(totp.current_valid_code(), "94287082");
+        assert!(totp.check(&totp.current_valid_code(), Some(0)));
+        assert!(totp.verify_strict(&totp.current_valid_code()));
+    }
+
+    #[test]
+    fn upcoming_codes_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let codes = totp.upcoming_codes(5);
+        assert_eq!(codes.len(), 5);
+        assert_eq!(codes[0], (1_111_111_110, totp.make()));
+        let current = 1_111_111_111 / 30;
+        for (i, (start, code)) in codes.iter().enumerate() {
+            assert_eq!(*start, (current + i as u64) * 30);
+            assert_eq!(*code, totp.code_at(current + i as u64, DEFAULT_DIGITS));
+        }
+        assert!(totp.upcoming_codes(0).is_empty());
+        let clock = FixedClock(u64::MAX);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        assert_eq!(totp.upcoming_codes(3).len(), 1);
+    }
+
+    #[test]
+    fn distinct_codes_in_test() {
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default);
+        let codes = totp.distinct_codes_in(0, 119);
+        assert_eq!(codes.len(), 4);
+        for (index, (range, code)) in codes.iter().enumerate() {
+            let period_start = index as u64 * 30;
+            assert_eq!(*range, period_start..=period_start + 29);
+            assert_eq!(*code, totp.make_time(period_start));
+        }
+        let codes = totp.distinct_codes_in(45, 70);
+        assert_eq!(codes.len(), 2);
+        assert_eq!(codes[0].0, 45..=59);
+        assert_eq!(codes[1].0, 60..=70);
+        assert!(totp.distinct_codes_in(70, 45).is_empty());
+        assert_eq!(totp.distinct_codes_in(u64::MAX, u64::MAX).len(), 1);
+        // With a single digit, codes repeat and consecutive repeats collapse.
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Digits(1));
+        let codes = totp.distinct_codes_in(0, 30 * 100 - 1);
+        assert!(codes.len() < 100);
+        assert!(codes
+            .windows(2)
+            .all(|pair| pair[0].1 != pair[1].1 && *pair[0].0.end() + 1 == *pair[1].0.start()));
+        assert_eq!(*codes[0].0.start(), 0);
+        assert_eq!(*codes[codes.len() - 1].0.end(), 30 * 100 - 1);
+    }
+
+    #[test]
+    fn make_with_validity_test() {
+        for &time in [0, 29, 30, 59, 1_111_111_111].iter() {
+            let clock = FixedClock(time);
+            let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+            let (otp, validity) = totp.make_with_validity();
+            assert_eq!(otp, totp.make());
+            assert!(validity.contains(&time));
+            assert_eq!(
+                validity.end() - validity.start() + 1,
+                constants::DEFAULT_PERIOD
+            );
+            assert_eq!(totp.make_time(*validity.start()), otp);
+            assert_eq!(totp.make_time(*validity.end()), otp);
+        }
+        let clock = FixedClock(u64::MAX);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        assert!(totp.make_with_validity().1.contains(&u64::MAX));
+    }
+
+    #[test]
+    fn stripped_leading_zeros_test() {
+        let clock = FixedClock(1_111_111_109);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert_eq!(totp.make(), "07081804");
+        assert!(!totp.verify_strict("7081804"));
+        assert_eq!(
+            totp.verify_detailed("7081804", 0),
+            VerifyResult::DigitCountMismatch {
+                submitted_len: 7,
+                expected_len: 8
+            }
+        );
+        let totp = totp.with_stripped_leading_zeros(true);
+        assert!(totp.check("7081804", Some(0)));
+        assert!(totp.check("07081804", Some(0)));
+        assert!(totp.verify_strict("7081804"));
+        assert_eq!(
+            totp.verify_detailed("7081804", 0),
+            VerifyResult::Valid { drift: 0 }
+        );
+        assert!(!totp.check("7081805", Some(0)));
+        assert!(!totp.check("", Some(0)));
+    }
+
+    #[test]
+    fn check_at_test() {
+        // The clock is far from the supplied time and must not be read.
+        let clock = FixedClock(0);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert_eq!(totp.check_at("94287082", 59, 0), Some(0));
+        assert_eq!(totp.check_at("94287082", 89, 1), Some(-1));
+        assert_eq!(totp.check_at("94287082", 29, 1), Some(1));
+        assert_eq!(totp.check_at("94287082", 119, 1), None);
+        assert_eq!(totp.check_at("07081804", 1_111_111_109, 0), Some(0));
+        assert!(!totp.check("07081804", Some(0)));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn verify_with_timestamp_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert_eq!(
+            totp.verify_with_timestamp("14050471", 1),
+            Some((0, "2005-03-18T01:58:30Z".to_string()))
+        );
+        assert_eq!(
+            totp.verify_with_timestamp(&totp.make_time(1_111_111_080), 1),
+            Some((-1, "2005-03-18T01:58:00Z".to_string()))
+        );
+        assert_eq!(totp.verify_with_timestamp("14050472", 1), None);
+        // The timestamp is the start of the matched step, fixed drift included.
+        let ahead = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock)
+        .with_fixed_drift(2);
+        assert_eq!(
+            ahead.verify_with_timestamp(&totp.make_time(1_111_111_170), 1),
+            Some((0, "2005-03-18T01:59:30Z".to_string()))
+        );
+        assert_eq!(
+            ahead.verify_with_timestamp(&totp.make_time(1_111_111_140), 1),
+            Some((-1, "2005-03-18T01:59:00Z".to_string()))
+        );
+    }
+
+    #[test]
+    fn check_suffix_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Default,
+        )
+        .with_clock(&clock);
+        assert_eq!(totp.make(), "287082");
+        assert_eq!(totp.check_suffix("hunter2287082", 1), Some("hunter2"));
+        assert_eq!(totp.check_suffix("287082", 1), Some(""));
+        assert_eq!(totp.check_suffix("pässwörd287082", 1), Some("pässwörd"));
+        assert_eq!(totp.check_suffix("hunter2123456", 1), None);
+        assert_eq!(totp.check_suffix("hunter2 287082", 1), Some("hunter2 "));
+        assert_eq!(totp.check_suffix("hunter2287 082", 1), None);
+        assert_eq!(totp.check_suffix("87082", 1), None);
+        assert_eq!(totp.check_suffix("hunterö", 1), None);
+        // A code outside the window is rejected.
+        let later = format!("hunter2{}", totp.make_drift(2));
+        assert_eq!(totp.check_suffix(&later, 1), None);
+        assert_eq!(totp.check_suffix(&later, 2), Some("hunter2"));
+    }
+
+    #[test]
+    fn display_debug_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert_eq!(format!("{}", totp), totp.make());
+        assert_eq!(totp.to_string(), "94287082");
+        let debug = format!("{:?}", totp);
+        assert!(debug.contains("REDACTED"));
+        assert!(debug.contains("SHA1"));
+        assert!(!debug.contains("49, 50, 51"));
+        assert!(!debug.contains("12345678901234567890"));
+    }
+
+    #[test]
+    fn check_with_algorithm_test() {
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890123456789012".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Digits(8)).with_clock(&clock);
+        // RFC 6238 SHA256 code at 59.
+        assert!(totp.check_with_algorithm("46119246", &ShaTypes::Sha2_256, 0));
+        assert!(!totp.check("46119246", Some(0)));
+        assert!(!totp.check_with_algorithm("46119246", &ShaTypes::Sha1, 0));
+        assert!(totp.check_with_algorithm(&totp.make(), &ShaTypes::Sha1, 0));
+        assert!(matches!(totp.algorithm, ShaTypes::Sha1));
+    }
+
+    #[test]
+    fn check_policy_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let strict_future = SkewPolicy {
+            back_steps: 1,
+            forward_steps: 0,
+            reject_future: true,
+        };
+        assert!(totp.check_policy(&totp.make(), &strict_future));
+        assert!(totp.check_policy(&totp.make_drift(-1), &strict_future));
+        assert!(!totp.check_policy(&totp.make_drift(-2), &strict_future));
+        assert!(!totp.check_policy(&totp.make_drift(1), &strict_future));
+        let ignored_forward = SkewPolicy {
+            forward_steps: 2,
+            ..strict_future
+        };
+        assert!(!totp.check_policy(&totp.make_drift(1), &ignored_forward));
+        let forward = SkewPolicy {
+            back_steps: 0,
+            forward_steps: 2,
+            reject_future: false,
+        };
+        assert!(totp.check_policy(&totp.make_drift(2), &forward));
+        assert!(!totp.check_policy(&totp.make_drift(-1), &forward));
+        assert!(totp.check_policy(&totp.make(), &SkewPolicy::default()));
+    }
+
+    #[test]
+    fn check_with_rtt_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        assert!(totp.check_with_rtt(&totp.make(), 0));
+        assert!(!totp.check_with_rtt(&totp.make_drift(-1), 0));
+        assert!(totp.check_with_rtt(&totp.make_drift(-1), 1));
+        assert!(totp.check_with_rtt(&totp.make_drift(-1), 31));
+        assert!(totp.check_with_rtt(&totp.make_drift(-2), 31));
+        assert!(!totp.check_with_rtt(&totp.make_drift(-3), 31));
+        assert!(!totp.check_with_rtt(&totp.make_drift(1), 31));
+    }
+
+    #[test]
+    fn check_with_grace_test() {
+        // The code of step 1 expired at 60, 2 seconds ago.
+        let clock = FixedClock(62);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let expired = totp.make_time(59);
+        assert!(totp.check_with_grace(&expired, 5));
+        assert!(totp.check_with_grace(&expired, 2));
+        assert!(!totp.check_with_grace(&expired, 1));
+        let boundary = FixedClock(60);
+        let totp_boundary =
+            Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&boundary);
+        assert!(!totp_boundary.check_with_grace(&expired, 0));
+        assert!(totp_boundary.check_with_grace(&expired, 1));
+        assert!(!totp.check_with_grace(&expired, 0));
+        assert!(totp.check_with_grace(&totp.make(), 0));
+        assert!(!totp.check_with_grace(&totp.make_time(29), 5));
+        assert!(!totp.check_with_grace(&totp.make_drift(1), 5));
+        assert!(totp.check_with_grace(&totp.make_time(0), u64::MAX));
+    }
+
+    #[test]
+    fn make_ascii_art_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert_eq!(totp.make(), "94287082");
+        let art = totp.make_ascii_art();
+        let lines: Vec<&str> = art.lines().collect();
+        assert_eq!(lines.len(), 5);
+        for (row, line) in lines.iter().enumerate() {
+            assert_eq!(line.len(), 8 * 4 - 1);
+            for (index, digit) in "94287082".chars().enumerate() {
+                let glyph = DIGIT_GLYPHS[digit.to_digit(10).unwrap() as usize][row];
+                assert_eq!(&line[index * 4..index * 4 + 3], glyph);
+            }
+        }
+        // The 7 at index 4 is drawn as a top bar and a right-hand stroke.
+        assert_eq!(&lines[0][16..19], "###");
+        assert_eq!(&lines[4][16..19], "  #");
+        assert_eq!(
+            ascii_art("1z"),
+            "  #    \n  #    \n  #  z \n  #    \n  #    "
+        );
+        assert_eq!(ascii_art(""), "\n\n\n\n");
+    }
+
+    #[test]
+    fn match_index_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let current = totp.make();
+        let previous = totp.make_drift(-1);
+        let candidates = [previous.as_str(), current.as_str(), current.as_str()];
+        assert_eq!(totp.match_index(&current, &candidates), Some(1));
+        assert_eq!(
+            totp.match_index(&format!(" {} ", current), &candidates),
+            Some(1)
+        );
+        // A candidate that is not the current code is not a match.
+        assert_eq!(totp.match_index(&previous, &candidates), None);
+        assert_eq!(totp.match_index(&previous, &[&previous]), None);
+        assert_eq!(totp.match_index("000000", &candidates), None);
+        assert_eq!(totp.match_index("", &[""]), None);
+        assert_eq!(totp.match_index(&current, &[]), None);
+    }
+
+    #[test]
+    fn check_with_policy_fn_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let drifted = totp.make_drift(-1);
+        assert!(totp.check_with_policy_fn(&drifted, 1, |_| true));
+        assert!(!totp.check_with_policy_fn(&drifted, 1, |context| context.drift == 0));
+        assert!(totp.check_with_policy_fn(&totp.make(), 1, |context| context.drift == 0));
+        let expected = VerifyContext {
+            drift: -1,
+            counter: 37_037_036,
+            matched_time: 1_111_111_080,
+            now: 1_111_111_111,
+        };
+        assert!(totp.check_with_policy_fn(&drifted, 1, |context| context == expected));
+        assert!(!totp.check_with_policy_fn("000000", 1, |_| true));
+        assert!(!totp.check_with_policy_fn(&drifted, 0, |_| true));
+        // The matched step includes the fixed drift, consistently with the counter.
+        let ahead = Totp::secret(vec![1, 2, 3], CreateOption::Default)
+            .with_clock(&clock)
+            .with_fixed_drift(2);
+        let expected = VerifyContext {
+            drift: -1,
+            counter: 37_037_038,
+            matched_time: 1_111_111_140,
+            now: 1_111_111_111,
+        };
+        let otp = totp.make_time(1_111_111_140);
+        assert!(ahead.check_with_policy_fn(&otp, 1, |context| context == expected));
+    }
+
+    #[test]
+    fn same_window_test() {
+        let clock = FixedClock(1_111_111_111);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let current = totp.make();
+        let old = totp.make_drift(-3);
+        assert!(totp.same_window(&current, &current, 1));
+        assert!(totp.same_window(&current, &totp.make_drift(-1), 1));
+        assert!(!totp.same_window(&current, &old, 1));
+        assert!(!totp.same_window(&old, &current, 1));
+        assert!(totp.same_window(&current, &old, 3));
+    }
+
+    #[test]
+    fn could_match_prefix_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        // RFC 6238 SHA1 code at 59 is 94287082.
+        assert!(totp.could_match_prefix("942", 0));
+        assert!(totp.could_match_prefix("9428 7082", 0));
+        assert!(!totp.could_match_prefix("943", 0));
+        assert!(!totp.could_match_prefix("942870820", 0));
+        assert!(!totp.could_match_prefix("", 1));
+        assert!(!totp.could_match_prefix(" \t ", 1));
+        // The code of the previous step, the RFC 4226 counter 0 value 1284755224.
+        assert_eq!(totp.make_drift(-1), "84755224");
+        assert!(!totp.could_match_prefix("847", 0));
+        assert!(totp.could_match_prefix("847", 1));
+    }
+
+    #[test]
+    fn check_duration_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let tolerance = Duration::from_secs(30);
+        assert!(totp.check_duration(&totp.make_drift(-1), tolerance));
+        assert!(totp.check_duration(&totp.make_drift(1), tolerance));
+        assert!(!totp.check_duration(&totp.make_drift(2), tolerance));
+        assert!(!totp.check_duration(&totp.make_drift(1), Duration::from_secs(29)));
+        assert!(totp.check_duration(&totp.make(), Duration::from_secs(0)));
+    }
+
+    struct FixedMillisClock(u128);
+
+    impl TimeSource for FixedMillisClock {
+        fn now_secs(&self) -> u64 {
+            (self.0 / 1000) as u64
+        }
+
+        fn now_millis(&self) -> u128 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn remaining_millis_test() {
+        let clock = FixedMillisClock(59_250);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        assert_eq!(totp.remaining_millis(), 750);
+        assert_eq!(totp.remaining(), 1);
+        let clock = FixedMillisClock(60_000);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Period(60)).with_clock(&clock);
+        assert_eq!(totp.remaining_millis(), 60_000);
+        // Sources without sub-second precision fall back to whole seconds.
+        let clock = FixedClock(59);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        assert_eq!(totp.remaining_millis(), 1_000);
+    }
+
+    #[test]
+    fn is_near_rollover_test() {
+        let near = FixedClock(58);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&near);
+        assert!(totp.is_near_rollover(2));
+        assert!(!totp.is_near_rollover(1));
+        let far = FixedClock(45);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&far);
+        assert!(!totp.is_near_rollover(2));
+        let start = FixedClock(60);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&start);
+        assert!(!totp.is_near_rollover(2));
+        assert!(totp.is_near_rollover(30));
+    }
+
+    #[test]
+    fn next_rollover_instant_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default).with_clock(&clock);
+        let before = Instant::now();
+        let rollover = totp.next_rollover_instant();
+        let after = Instant::now();
+        assert!(rollover >= before + Duration::from_secs(1));
+        assert!(rollover <= after + Duration::from_secs(1));
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default);
+        let now = Instant::now();
+        let rollover = totp.next_rollover_instant();
+        assert!(rollover > now);
+        assert!(rollover <= Instant::now() + Duration::from_secs(constants::DEFAULT_PERIOD));
+    }
+
+    #[test]
+    fn seconds_until_past_counter_test() {
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default);
+        let time = 1_111_111_109;
+        let past = time / totp.period - 1;
+        assert_eq!(totp.seconds_until_counter_at(past, time), None);
+        assert_eq!(totp.seconds_until_counter(0), None);
+    }
+
+    #[test]
+    fn verify_history_test() {
+        let clock = FixedClock(1_111_111_109);
+        let totp = |secret: &str| {
+            Totp::secret(secret.as_bytes().to_vec(), CreateOption::Default).with_clock(&clock)
+        };
+        let current = totp("Current secret");
+        let history = [
+            totp("First secret"),
+            totp("Second secret"),
+            totp("Third secret"),
+        ];
+        let otp = history[2].make_time(1_111_111_109 - 30);
+        assert_eq!(current.verify_history(&otp, &history, 1), Some(2));
+        assert_eq!(current.verify_history(&otp, &history, 0), None);
+        assert_eq!(current.verify_history(&current.make(), &history, 1), None);
+    }
+
+    #[test]
+    fn enroll_code_test() {
+        let clock = FixedClock(59);
         let secret = "12345678901234567890123456789012".as_bytes().to_vec();
         let totp = Totp::secret(
             secret,
@@ -225,35 +2761,686 @@ mod tests {
                 period: constants::DEFAULT_PERIOD,
                 algorithm: &hmacsha::ShaTypes::Sha2_256,
             },
+        )
+        .with_clock(&clock);
+        assert_eq!(totp.enroll_code(), "46119246");
+        assert_eq!(totp.enroll_code(), totp.make());
+    }
+
+    #[test]
+    fn check_separators_test() {
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert_eq!(totp.make(), "287082");
+        assert!(totp.check("287-082", Some(0)));
+        assert!(totp.check("28\n7082", Some(0)));
+        assert!(!totp.check("287 082x", Some(0)));
+        let history = [Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Default,
+        )];
+        assert_eq!(totp.verify_history("287-082", &history, 0), Some(0));
+    }
+
+    #[test]
+    fn diagnose_match_test() {
+        let clock = FixedClock(1_111_111_109);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        let otp = totp.make_drift(10);
+        assert_eq!(
+            totp.diagnose(&otp, 20),
+            DiagnoseResult::Matched { drift: 10 }
+        );
+        let otp = totp.make_drift(-3);
+        assert_eq!(
+            totp.diagnose(&otp, 20),
+            DiagnoseResult::Matched { drift: -3 }
         );
-        let code = totp.make_time(59);
-        assert_eq!(code, "46119246");
-        let code = totp.make_time(1_111_111_109);
-        assert_eq!(code, "68084774");
-        let code = totp.make_time(1_111_111_111);
-        assert_eq!(code, "67062674");
-        let code = totp.make_time(1_234_567_890);
-        assert_eq!(code, "91819424");
-        let code = totp.make_time(2_000_000_000);
-        assert_eq!(code, "90698825");
-        let code = totp.make_time(20_000_000_000);
-        assert_eq!(code, "77737706");
     }
 
     #[test]
-    fn check_test() {
+    fn diagnose_not_found_test() {
+        let clock = FixedClock(1_111_111_109);
         let secret = "A strong shared secret".as_bytes().to_vec();
-        let totp = Totp::secret(secret, CreateOption::Default);
-        let code = totp.make(0);
-        assert!(totp.check(code.as_str(), None))
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        let otp = totp.make_drift(10);
+        let counter = 1_111_111_109 / 30;
+        assert_eq!(
+            totp.diagnose(&otp, 5),
+            DiagnoseResult::NotFound {
+                first_counter: counter - 5,
+                last_counter: counter + 5,
+            }
+        );
+    }
+
+    #[cfg(feature = "sss")]
+    #[test]
+    fn from_shares_test() {
+        use crate::sss::tests::split;
+
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890".as_bytes();
+        let shares = split(secret, &[0x07, 0xa9], 4);
+        let totp = Totp::from_shares(&shares[1..], 3, CreateOption::Digits(8))
+            .unwrap()
+            .with_clock(&clock);
+        assert_eq!(totp.make(), "94287082");
+        assert!(totp.hotp.wipe_on_drop);
+        assert!(
+            !Totp::secret(secret.to_vec(), CreateOption::Default)
+                .hotp
+                .wipe_on_drop
+        );
+        assert!(Totp::from_shares(&shares[..2], 3, CreateOption::Default).is_err());
+    }
+
+    #[test]
+    fn verify_detailed_test() {
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Digits(8)).with_clock(&clock);
+        assert_eq!(
+            totp.verify_detailed("94287082", 1),
+            VerifyResult::Valid { drift: 0 }
+        );
+        assert_eq!(
+            totp.verify_detailed("287082", 1),
+            VerifyResult::DigitCountMismatch {
+                submitted_len: 6,
+                expected_len: 8,
+            }
+        );
+        assert_eq!(totp.verify_detailed("123456", 1), VerifyResult::Invalid);
+        assert_eq!(totp.verify_detailed("12345678", 1), VerifyResult::Invalid);
+    }
+
+    #[test]
+    fn verify_at_with_freshness_test() {
+        let clock = FixedClock(1_111_111_200);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        let claimed_time = 1_111_111_109;
+        let otp = totp.make_time(claimed_time);
+        assert!(totp.verify_at_with_freshness(&otp, claimed_time, 120));
+        assert!(!totp.verify_at_with_freshness(&otp, claimed_time, 60));
+        assert!(!totp.verify_at_with_freshness(&otp, claimed_time + 30, 120));
+        // A claimed time in the future is rejected, not treated as fresh.
+        let future = 1_111_111_200 + 3_600;
+        let otp = totp.make_time(future);
+        assert!(!totp.verify_at_with_freshness(&otp, future, 120));
+        assert_eq!(
+            totp.verify_freshness(&otp, future, 120, 60),
+            Err(FreshnessError::FromFuture { ahead: 3_600 })
+        );
+        let ahead = 1_111_111_200 + 20;
+        let otp = totp.make_time(ahead);
+        assert!(!totp.verify_at_with_freshness(&otp, ahead, 120));
+        assert_eq!(totp.verify_freshness(&otp, ahead, 120, 60), Ok(()));
+        assert_eq!(
+            totp.verify_freshness(&otp, claimed_time, 60, 60),
+            Err(FreshnessError::Stale { age: 91 })
+        );
+        assert_eq!(
+            totp.verify_freshness("000000", 1_111_111_200, 60, 60),
+            Err(FreshnessError::Invalid)
+        );
+    }
+
+    #[test]
+    fn check_trailing_newline_test() {
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert!(totp.check("287082\n", Some(0)));
+        assert!(totp.check("287082\r\n", Some(0)));
+        assert_eq!(
+            totp.verify_detailed("287082\n", 0),
+            VerifyResult::Valid { drift: 0 }
+        );
+        assert_eq!(
+            totp.diagnose("287082\n", 0),
+            DiagnoseResult::Matched { drift: 0 }
+        );
+        assert!(totp.verify_at_with_freshness("287082\r\n", 59, 0));
+    }
+
+    #[test]
+    fn make_days_offset_test() {
+        // 2005-03-18T01:58:31Z.
+        let clock = FixedClock(1_111_111_111);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let daily = Totp::secret(secret.clone(), CreateOption::Period(86_400)).with_clock(&clock);
+        let today = 1_111_111_111 / 86_400 * 86_400;
+        assert_eq!(daily.make_days_offset(0), daily.make_time(today));
+        assert_eq!(daily.make_days_offset(1), daily.make_time(today + 86_400));
+        assert_eq!(daily.make_days_offset(-1), daily.make_time(today - 86_400));
+        assert_eq!(daily.make_days_offset(1), daily.make_drift(1));
+        assert_ne!(daily.make_days_offset(1), daily.make_days_offset(0));
+        assert_eq!(daily.make_days_offset(i64::MIN), daily.make_time(0));
+        assert_eq!(daily.make_days_offset(i64::MAX), daily.make_time(u64::MAX));
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert_eq!(
+            totp.make_days_offset(1),
+            totp.make_time(1_111_111_111 + 86_400)
+        );
+    }
+
+    #[test]
+    fn oversized_input_test() {
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        let padded = format!("287082{}", " ".repeat(10_000));
+        assert!(!totp.check(&padded, Some(30)));
+        assert!(!totp.verify_strict(&padded));
+        assert_eq!(totp.verify_detailed(&padded, 1), VerifyResult::Invalid);
+        assert!(!totp.could_match_prefix(&padded, 1));
+        assert!(totp.check(&padded[..MAX_OTP_INPUT_LEN], Some(30)));
+    }
+
+    /// The counter must only depend on Unix seconds, which DST transitions and leap
+    /// seconds do not affect, and never on local time.
+    #[test]
+    fn unix_time_counter_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let hotp = crate::hotp::Hotp::new(secret.clone());
+        // US DST start and end in 2021, and the 2016-12-31 leap second.
+        for &instant in [1_615_705_200_u64, 1_636_264_800, 1_483_228_800].iter() {
+            for time in instant - 31..=instant + 31 {
+                let clock = FixedClock(time);
+                let totp = Totp::secret(secret.clone(), CreateOption::Default).with_clock(&clock);
+                let expected = hotp.make(crate::hotp::MakeOption::Counter(time / 30));
+                assert_eq!(totp.make(), expected);
+                assert_eq!(totp.remaining(), 30 - time % 30);
+            }
+            // An hour of wall-clock time is always 120 steps, even across a DST change.
+            let clock = FixedClock(instant + 1_800);
+            let totp = Totp::secret(secret.clone(), CreateOption::Default).with_clock(&clock);
+            let expected = hotp.make(crate::hotp::MakeOption::Counter(
+                (instant - 1_800) / 30 + 120,
+            ));
+            assert_eq!(totp.make(), expected);
+        }
+    }
+
+    #[test]
+    fn self_test_test() {
+        use hmacsha::ShaTypes;
+
+        let algorithms = [
+            ShaTypes::Sha1,
+            ShaTypes::Sha2_256,
+            ShaTypes::Sha2_512,
+            ShaTypes::Sha3_256,
+            ShaTypes::Sha3_512,
+        ];
+        for algorithm in algorithms.iter() {
+            for &digits in [6, 8, 10].iter() {
+                let secret = "A strong shared secret".as_bytes().to_vec();
+                let totp = Totp::secret(
+                    secret,
+                    CreateOption::Full {
+                        digits,
+                        period: constants::DEFAULT_PERIOD,
+                        algorithm,
+                    },
+                );
+                assert_eq!(totp.self_test(), Ok(()));
+            }
+        }
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Digits(0));
+        assert_eq!(totp.self_test(), Err(SelfTestError::MalformedCode));
+        // Non-decimal codes are verified too.
+        for &radix in [2, 16, 36].iter() {
+            let secret = "A strong shared secret".as_bytes().to_vec();
+            let totp = Totp::secret(secret, CreateOption::Digits(8)).with_radix(radix);
+            assert_eq!(totp.self_test(), Ok(()));
+            let code = totp.code_at(1, 8);
+            let encoder = |value| format_code_radix(value, 8, radix);
+            let check = CheckOption::Full {
+                counter: 1,
+                breadth: 0,
+                algorithm: totp.algorithm,
+            };
+            assert!(totp.hotp.check_with(&code, encoder, check));
+            let mut tampered = code.into_bytes();
+            tampered[0] = if tampered[0] == b'1' { b'0' } else { b'1' };
+            let tampered = String::from_utf8(tampered).unwrap();
+            assert!(!totp.hotp.check_with(&tampered, encoder, check));
+        }
+    }
+
+    #[test]
+    fn verify_strict_test() {
+        let clock = FixedClock(1_111_111_109);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert!(totp.verify_strict(&totp.make()));
+        let previous = totp.make_drift(-1);
+        assert!(!totp.verify_strict(&previous));
+        assert!(totp.check(&previous, Some(1)));
+    }
+
+    #[test]
+    fn effective_acceptance_secs_test() {
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default);
+        assert_eq!(totp.effective_acceptance_secs(0), 30);
+        assert_eq!(totp.effective_acceptance_secs(2), 150);
+        assert_eq!(totp.effective_acceptance_secs(u64::MAX), u64::MAX);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Period(0));
+        assert_eq!(totp.effective_acceptance_secs(1), 3);
+    }
+
+    #[test]
+    fn expected_attempts_to_break_test() {
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default);
+        assert_eq!(totp.expected_attempts_to_break(), 500_000.0);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Digits(8));
+        assert_eq!(totp.expected_attempts_to_break(), 50_000_000.0);
+        assert_eq!(
+            totp.with_radix(16).expected_attempts_to_break(),
+            2_147_483_648.0
+        );
+    }
+
+    #[test]
+    fn valid_codes_at_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Digits(8));
+        let codes = totp.valid_codes_at(59, 1);
+        assert_eq!(
+            codes.reveal(),
+            [
+                totp.make_time(0),
+                "94287082".to_string(),
+                totp.make_time(60)
+            ]
+        );
+        assert_eq!(format!("{:?}", codes), "ValidCodes(REDACTED, 3 codes)");
+        assert_eq!(codes.to_string(), "3 REDACTED codes");
+        assert!(!format!("{:?} {}", codes, codes).contains("94287082"));
+        assert_eq!(totp.valid_codes_at(0, 1).reveal().len(), 2);
+    }
+
+    #[test]
+    fn window_counters_test() {
+        let clock = FixedClock(1_111_111_109);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert_eq!(
+            totp.window_counters(2),
+            [37_037_034, 37_037_035, 37_037_036, 37_037_037, 37_037_038]
+        );
+        assert_eq!(totp.window_counters(0), [37_037_036]);
+    }
+
+    #[test]
+    fn window_counters_boundary_test() {
+        let clock = FixedClock(45);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert_eq!(totp.window_counters(2), [0, 1, 2, 3]);
+        assert_eq!(
+            totp.window_counters_at(u64::MAX, 1),
+            [u64::MAX / 30 - 1, u64::MAX / 30, u64::MAX / 30 + 1]
+        );
+        let totp = Totp::secret("secret".as_bytes().to_vec(), CreateOption::Period(1));
+        assert_eq!(
+            totp.window_counters_at(u64::MAX, 1),
+            [u64::MAX - 1, u64::MAX]
+        );
+    }
+
+    #[test]
+    fn from_base32_test() {
+        let clock = FixedClock(59);
+        let totp = Totp::from_base32("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", CreateOption::Digits(8))
+            .unwrap()
+            .with_clock(&clock);
+        assert_eq!(totp.make(), "94287082");
+        assert!(matches!(
+            Totp::from_base32("JBSWY3DPEHPK3PX", CreateOption::Default),
+            Err(DecodeError::InvalidLength)
+        ));
+        assert!(matches!(
+            Totp::from_base32("JBSWY3DPEHPK3PX1", CreateOption::Default),
+            Err(DecodeError::InvalidCharacter('1'))
+        ));
+        let grouped = Totp::from_base32(
+            " gezd GNBV\tgy3t qojq gezd gnbv gy3t qojq\n",
+            CreateOption::Digits(8),
+        )
+        .unwrap()
+        .with_clock(&clock);
+        assert_eq!(grouped.make(), "94287082");
+        assert_eq!(
+            grouped.hotp.secret_base32(),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+        );
+        for &invalid in ['0', '1', '8', '9'].iter() {
+            let secret = format!("JBSW Y3DP EHPK 3PX{}", invalid);
+            assert!(matches!(
+                Totp::from_base32(&secret, CreateOption::Default),
+                Err(DecodeError::InvalidCharacter(c)) if c == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn from_auto_test() {
+        let clock = FixedClock(59);
+        let hex = "3132333435363738393031323334353637383930";
+        let base32 = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        for secret in [hex, base32].iter() {
+            let totp = Totp::from_auto(secret, CreateOption::Digits(8))
+                .unwrap()
+                .with_clock(&clock);
+            assert_eq!(totp.make(), "94287082");
+        }
+        let totp = Totp::from_auto("DEADBEEF", CreateOption::Default).unwrap();
+        assert_eq!(totp.hotp.secret(), [0xde, 0xad, 0xbe, 0xef]);
+        assert!(Totp::from_auto("0189", CreateOption::Default).is_ok());
+        assert!(Totp::from_auto("01890", CreateOption::Default).is_err());
+    }
+
+    #[test]
+    fn verify_learn_test() {
+        use hmacsha::ShaTypes;
+
+        let clock = FixedClock(1_111_111_109);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        let app = Totp::secret(secret.clone(), CreateOption::Algorithm(&ShaTypes::Sha2_256))
+            .with_clock(&clock);
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        let otp = app.make_drift(1);
+        let algorithms = [&ShaTypes::Sha1, &ShaTypes::Sha2_256, &ShaTypes::Sha2_512];
+        let learned = totp.verify_learn(&otp, &algorithms, 1);
+        assert!(matches!(learned, Some((ShaTypes::Sha2_256, 1))));
+        assert!(totp.verify_learn(&otp, &algorithms, 0).is_none());
+        assert!(totp.verify_learn(&otp, &algorithms[..1], 1).is_none());
+    }
+
+    #[test]
+    fn verify_learn_period_test() {
+        let clock = FixedClock(1_111_111_109);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let app = Totp::secret(secret.clone(), CreateOption::Period(60)).with_clock(&clock);
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        let otp = app.make();
+        assert_ne!(otp, totp.make());
+        assert_eq!(totp.verify_learn_period(&otp, &[30, 60], 1), Some((60, 0)));
+        assert_eq!(
+            totp.verify_learn_period(&app.make_drift(-1), &[0, 30, 60], 1),
+            Some((60, -1))
+        );
+        assert_eq!(
+            totp.verify_learn_period(&totp.make(), &[30, 60], 1),
+            Some((30, 0))
+        );
+        assert_eq!(totp.verify_learn_period(&otp, &[30], 1), None);
+        assert_eq!(totp.verify_learn_period(&otp, &[], 1), None);
+    }
+
+    #[test]
+    fn fixed_drift_test() {
+        let clock = FixedClock(1_111_111_109);
+        let secret = "A strong shared secret".as_bytes().to_vec();
+        // The token's clock is three steps behind.
+        let token = Totp::secret(secret.clone(), CreateOption::Default)
+            .with_clock(&FixedClock(1_111_111_109 - 90));
+        let otp = token.make();
+        let totp = Totp::secret(secret.clone(), CreateOption::Default).with_clock(&clock);
+        assert!(!totp.check(&otp, Some(0)));
+        let totp = totp.with_fixed_drift(-3);
+        assert_eq!(totp.make(), otp);
+        assert!(totp.check(&otp, Some(0)));
+        assert!(totp.verify_strict(&otp));
+        assert_eq!(
+            totp.verify_detailed(&otp, 1),
+            VerifyResult::Valid { drift: 0 }
+        );
+        assert_eq!(totp.window_counters(0), [1_111_111_109 / 30 - 3]);
+        assert_eq!(totp.seconds_until_counter(1_111_111_109 / 30 - 2), Some(1));
+    }
+
+    #[test]
+    fn verify_matched_code_test() {
+        let clock = FixedClock(59);
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret, CreateOption::Default).with_clock(&clock);
+        assert_eq!(
+            totp.verify_matched_code("287 082", 1),
+            Some("287082".to_string())
+        );
+        // RFC 4226 code of counter 0, one step behind.
+        assert_eq!(
+            totp.verify_matched_code(" 755224\t", 1),
+            Some("755224".to_string())
+        );
+        assert_eq!(totp.verify_matched_code("123 456", 1), None);
+    }
+
+    #[test]
+    fn multi_secret_verifier_test() {
+        let clock = FixedClock(1_111_111_111);
+        let secrets = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let config = OtpConfig {
+            digits: 8,
+            ..OtpConfig::default()
+        };
+        let verifier = MultiSecretVerifier::new(config, secrets.clone()).with_clock(&clock);
+        assert_eq!(verifier.totps.len(), 3);
+        let otp = Totp::from_config(secrets[1].clone(), config)
+            .with_clock(&clock)
+            .make_drift(-1);
+        assert_eq!(verifier.verify(&otp, 1), Some(1));
+        assert_eq!(verifier.verify(&otp, 0), None);
+        assert_eq!(verifier.verify("00000000", 1), None);
+        let empty = MultiSecretVerifier::new(config, Vec::new());
+        assert_eq!(empty.verify(&otp, 1), None);
+    }
+
+    struct CountingClock(u64, AtomicUsize);
+
+    impl TimeSource for CountingClock {
+        fn now_secs(&self) -> u64 {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            self.0
+        }
+    }
+
+    #[test]
+    fn multi_secret_verifier_uniform_test() {
+        let secrets = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        for position in 0..secrets.len() {
+            let clock = CountingClock(1_111_111_111, AtomicUsize::new(0));
+            let verifier =
+                MultiSecretVerifier::new(OtpConfig::default(), secrets.clone()).with_clock(&clock);
+            let otp = verifier.totps[position].make();
+            clock.1.store(0, Ordering::SeqCst);
+            assert_eq!(verifier.verify(&otp, 1), Some(position));
+            // Every secret is verified, whichever matched.
+            assert_eq!(clock.1.load(Ordering::SeqCst), secrets.len());
+        }
+        let clock = CountingClock(1_111_111_111, AtomicUsize::new(0));
+        let verifier =
+            MultiSecretVerifier::new(OtpConfig::default(), secrets.clone()).with_clock(&clock);
+        assert_eq!(verifier.verify("not a code", 1), None);
+        assert_eq!(clock.1.load(Ordering::SeqCst), secrets.len());
+        // Two secrets sharing a code: the first one is reported.
+        let twins = vec![vec![1, 2, 3], vec![4, 5, 6], vec![4, 5, 6]];
+        let verifier = MultiSecretVerifier::new(OtpConfig::default(), twins).with_clock(&clock);
+        assert_eq!(verifier.verify(&verifier.totps[2].make(), 1), Some(1));
+    }
+
+    #[test]
+    fn diagnose_any_test() {
+        let secret = "12345678901234567890123456789012".as_bytes();
+        // RFC 6238 SHA256 vector at T = 59.
+        let matches = diagnose_any(secret, "46119246", 59, 1);
+        let expected = OtpConfig {
+            digits: 8,
+            period: 30,
+            algorithm: &ShaTypes::Sha2_256,
+            ..OtpConfig::default()
+        };
+        assert_eq!(matches[0], (expected, 0));
+        // Step 1 is also the next step with a 60 seconds period.
+        let sixty = OtpConfig {
+            period: 60,
+            ..expected
+        };
+        assert_eq!(matches, [(expected, 0), (sixty, 1)]);
+        assert_eq!(diagnose_any(secret, "46119246", 59, 0), [(expected, 0)]);
+        assert_eq!(diagnose_any(secret, "46119246", 89, 1)[0], (expected, -1));
+        assert!(diagnose_any(secret, "46119246", 120, 0).is_empty());
+        assert!(diagnose_any(b"another secret", "46119246", 59, 1).is_empty());
+    }
+
+    #[test]
+    fn parse_keyed_code_test() {
+        assert_eq!(parse_keyed_code("3;123456"), Some((3, "123456")));
+        assert_eq!(parse_keyed_code("kid=3;123456"), Some((3, "123456")));
+        assert_eq!(parse_keyed_code(" 42 ;123 456"), Some((42, "123 456")));
+        assert_eq!(parse_keyed_code("123456"), None);
+        assert_eq!(parse_keyed_code("x;123456"), None);
+        assert_eq!(parse_keyed_code("-3;123456"), None);
+        assert_eq!(parse_keyed_code(";123456"), None);
+    }
+
+    #[test]
+    fn keyed_verifier_test() {
+        let clock = FixedClock(59);
+        let mut totps = std::collections::HashMap::new();
+        totps.insert(
+            3,
+            Totp::secret(
+                "12345678901234567890".as_bytes().to_vec(),
+                CreateOption::Default,
+            )
+            .with_clock(&clock),
+        );
+        totps.insert(
+            7,
+            Totp::secret(
+                "A strong shared secret".as_bytes().to_vec(),
+                CreateOption::Default,
+            )
+            .with_clock(&clock),
+        );
+        let verifier = KeyedVerifier { totps, window: 0 };
+        assert_eq!(verifier.verify("3;287082"), Some(3));
+        assert_eq!(verifier.verify("kid=3;287082"), Some(3));
+        assert_eq!(verifier.verify("7;287082"), None);
+        assert_eq!(verifier.verify("4;287082"), None);
+        assert_eq!(verifier.verify("287082"), None);
+        let otp = verifier.totps[&7].make();
+        assert_eq!(verifier.verify(&format!("7;{}", otp)), Some(7));
+    }
+
+    #[test]
+    fn verify_batch_test() {
+        let clock = FixedClock(1_111_111_109);
+        let totp = |secret: &str| {
+            Totp::secret(secret.as_bytes().to_vec(), CreateOption::Default).with_clock(&clock)
+        };
+        let alice = totp("Alice's secret");
+        let bob = totp("Bob's secret");
+        let entries = vec![
+            (totp("Alice's secret"), alice.make()),
+            (totp("Bob's secret"), alice.make()),
+            (totp("Bob's secret"), bob.make_drift(-1)),
+            (totp("Carol's secret"), "12345".to_string()),
+            (totp("Bob's secret"), bob.make_drift(-2)),
+        ];
+        assert_eq!(verify_batch(&entries, 1), [true, false, true, false, false]);
+        assert!(verify_batch(&[], 1).is_empty());
+    }
+
+    #[test]
+    fn freeotp_default_test() {
+        // FreeOTP implements RFC 6238 unmodified: these are the RFC SHA1 vectors at
+        // 6 digits, and the code of the common JBSWY3DPEHPK3PXP demo secret.
+        let totp = Totp::freeotp_default("12345678901234567890".as_bytes().to_vec());
+        let vectors = [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_111_111_111, "050471"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+            (20_000_000_000, "353130"),
+        ];
+        for &(time, code) in vectors.iter() {
+            assert_eq!(totp.make_time(time), code);
+        }
+        let totp = Totp::freeotp_default(b"Hello!\xDE\xAD\xBE\xEF".to_vec());
+        assert_eq!(totp.make_time(1_700_000_000), "324550");
+        assert_eq!((totp.digits, totp.period), (6, 30));
+        assert!(matches!(totp.algorithm, ShaTypes::Sha1));
+    }
+
+    #[test]
+    fn config_summary_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::secret(secret.clone(), CreateOption::Default);
+        assert_eq!(totp.config_summary(), "TOTP SHA1, 6 digits, 30s period");
+        let totp = Totp::secret(
+            secret.clone(),
+            CreateOption::Full {
+                digits: 8,
+                period: 60,
+                algorithm: &ShaTypes::Sha2_256,
+            },
+        );
+        assert_eq!(totp.config_summary(), "TOTP SHA256, 8 digits, 60s period");
+        assert!(!totp.config_summary().contains("1234"));
+        let totp = Totp::secret(secret, CreateOption::Digits(8)).with_radix(16);
+        assert_eq!(
+            totp.config_summary(),
+            "TOTP SHA1, 8 digits, 30s period, base 16"
+        );
+    }
+
+    #[test]
+    fn radix_end_to_end_test() {
+        let clock = FixedClock(59);
+        let config = OtpConfig {
+            digits: 8,
+            radix: 16,
+            ..OtpConfig::default()
+        };
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        let totp = Totp::from_config(secret, config).with_clock(&clock);
+        assert_eq!(totp.config(), config);
+        // 0x41397eea is the truncated value behind the RFC 6238 SHA1 vector 94287082.
+        let otp = totp.make();
+        assert_eq!(otp, "41397eea");
+        assert!(totp.check(&otp, Some(0)));
+        assert!(totp.verify_strict(&otp));
+        assert_eq!(
+            totp.verify_detailed(&otp, 1),
+            VerifyResult::Valid { drift: 0 }
+        );
+        assert_eq!(totp.self_test(), Ok(()));
+        let decimal = Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Digits(8),
+        )
+        .with_clock(&clock);
+        assert!(!decimal.check(&otp, Some(0)));
+        assert!(!totp.check("94287082", Some(0)));
     }
 
     #[test]
     fn rapid_make_test() {
         let secret = "A strong shared secret".as_bytes().to_vec();
         let totp = Totp::secret(secret, CreateOption::Default);
-        let code1 = totp.make(0);
-        let code2 = totp.make(0);
+        let code1 = totp.make();
+        let code2 = totp.make();
         assert!(totp.check(code1.as_str(), None));
         assert!(totp.check(code2.as_str(), None));
         assert_eq!(code1, code2);
@@ -0,0 +1,672 @@
+use crate::account::OtpType;
+use crate::algorithm::{algorithm_label, parse_algorithm, OtpAlgorithmId};
+use crate::constants::{
+    DEFAULT_COUNTER, DEFAULT_DIGITS, DEFAULT_PERIOD, DEFAULT_RADIX, MAX_DIGITS,
+};
+use crate::encoding::{decode_base32, encode_base32, DecodeError};
+use crate::hotp::Hotp;
+use crate::totp::{CreateOption, Totp};
+use hmacsha::ShaTypes;
+use std::fmt;
+
+/// The errors returned when parsing an `otpauth://` provisioning URI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UriError {
+    /// The scheme is not `otpauth`.
+    Scheme,
+    /// The type segment is neither `totp` nor `hotp`, or not the expected one.
+    UnsupportedType(String),
+    /// The label or a parameter is not valid percent-encoded UTF-8.
+    Encoding,
+    /// The `secret` parameter is missing.
+    MissingSecret,
+    /// The secret is not valid Base32.
+    Secret(DecodeError),
+    /// The algorithm label is not supported.
+    UnsupportedAlgorithm(String),
+    /// A numeric parameter is malformed or out of range.
+    InvalidParameter { name: String, value: String },
+    /// The `counter` parameter of an HOTP URI is missing.
+    MissingCounter,
+}
+
+impl fmt::Display for UriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UriError::Scheme => write!(f, "the scheme is not otpauth"),
+            UriError::UnsupportedType(otp_type) => write!(f, "unsupported type {:?}", otp_type),
+            UriError::Encoding => write!(f, "invalid percent-encoding"),
+            UriError::MissingSecret => write!(f, "missing secret"),
+            UriError::Secret(error) => write!(f, "invalid secret: {}", error),
+            UriError::UnsupportedAlgorithm(label) => {
+                write!(f, "unsupported algorithm {:?}", label)
+            }
+            UriError::InvalidParameter { name, value } => {
+                write!(f, "invalid {} {:?}", name, value)
+            }
+            UriError::MissingCounter => write!(f, "missing counter"),
+        }
+    }
+}
+
+impl std::error::Error for UriError {}
+
+impl From<DecodeError> for UriError {
+    fn from(error: DecodeError) -> Self {
+        UriError::Secret(error)
+    }
+}
+
+/**
+The content of an `otpauth://` provisioning URI, as exchanged with authenticator apps
+through QR codes. Its `Display` output is the URI, and its `Debug` output redacts the secret.
+
+Any positive period is kept, even a non-standard one such as 45 seconds.
+
+The `radix`, `drift` and `salt` parameters are non-standard extensions describing a `Totp`
+with a non-decimal radix, a fixed drift or a per-account salt: authenticator apps ignore
+them and compute different codes. They are only emitted when they differ from their
+defaults, `10`, `0` and no salt.
+
+# Example
+
+```
+use ootp::uri::OtpUri;
+
+let uri = OtpUri::parse("otpauth://hotp/ACME:alice?secret=JBSWY3DPEHPK3PXP&counter=7").unwrap();
+assert_eq!(uri.issuer, "ACME");
+assert_eq!(uri.counter, 7);
+assert_eq!(uri.to_string(), "otpauth://hotp/ACME:alice?secret=JBSWY3DPEHPK3PXP&issuer=ACME&algorithm=SHA1&digits=6&counter=7");
+```
+*/
+#[derive(Clone, PartialEq, Eq)]
+pub struct OtpUri {
+    pub otp_type: OtpType,
+    pub issuer: String,
+    pub account: String,
+    pub secret: Vec<u8>,
+    pub algorithm: OtpAlgorithmId,
+    pub digits: u32,
+    /// The period, in seconds, of a TOTP URI.
+    pub period: u64,
+    /// The counter of an HOTP URI.
+    pub counter: u64,
+    /// The radix of the codes, `10` unless the non-standard `radix` parameter is set.
+    pub radix: u32,
+    /// The fixed drift, in steps, of a TOTP URI, `0` unless the non-standard `drift`
+    /// parameter is set.
+    pub drift: i64,
+    /// The per-account salt, see `Hotp::with_salt`, Base32 encoded in the non-standard
+    /// `salt` parameter.
+    pub salt: Vec<u8>,
+}
+
+impl fmt::Debug for OtpUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtpUri")
+            .field("otp_type", &self.otp_type)
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .field("secret", &"REDACTED")
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("counter", &self.counter)
+            .field("radix", &self.radix)
+            .field("drift", &self.drift)
+            .field("salt", &self.salt)
+            .finish()
+    }
+}
+
+impl fmt::Display for OtpUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let otp_type = match self.otp_type {
+            OtpType::Totp => "totp",
+            OtpType::Hotp => "hotp",
+        };
+        write!(f, "otpauth://{}/", otp_type)?;
+        if !self.issuer.is_empty() {
+            write!(f, "{}:", percent_encode(&self.issuer))?;
+        }
+        write!(
+            f,
+            "{}?secret={}",
+            percent_encode(&self.account),
+            encode_base32(&self.secret)
+        )?;
+        if !self.issuer.is_empty() {
+            write!(f, "&issuer={}", percent_encode(&self.issuer))?;
+        }
+        write!(
+            f,
+            "&algorithm={}&digits={}",
+            percent_encode(algorithm_label(self.algorithm.sha_type())),
+            self.digits
+        )?;
+        match self.otp_type {
+            OtpType::Totp => write!(f, "&period={}", self.period)?,
+            OtpType::Hotp => write!(f, "&counter={}", self.counter)?,
+        }
+        if self.radix != DEFAULT_RADIX {
+            write!(f, "&radix={}", self.radix)?;
+        }
+        if self.drift != 0 {
+            write!(f, "&drift={}", self.drift)?;
+        }
+        if !self.salt.is_empty() {
+            write!(f, "&salt={}", encode_base32(&self.salt))?;
+        }
+        Ok(())
+    }
+}
+
+impl OtpUri {
+    /**
+    Parses an `otpauth://totp/` or `otpauth://hotp/` URI.
+
+    The label is `issuer:account` or `account`, percent-encoded; an `issuer` parameter
+    takes precedence over the issuer of the label. Missing `algorithm`, `digits` and
+    `period` parameters take their default values, unknown parameters are ignored.
+    A `period` of `0` and a `radix` outside of `2..=36` are rejected.
+    HOTP URIs require a `counter` parameter.
+    */
+    pub fn parse(uri: &str) -> Result<Self, UriError> {
+        let rest = strip_prefix_ignore_case(uri, "otpauth://").ok_or(UriError::Scheme)?;
+        let (path, query) = match rest.find('?') {
+            Some(index) => (&rest[..index], &rest[index + 1..]),
+            None => (rest, ""),
+        };
+        let (otp_type, label) = match path.find('/') {
+            Some(index) => (&path[..index], &path[index + 1..]),
+            None => (path, ""),
+        };
+        let otp_type = if otp_type.eq_ignore_ascii_case("totp") {
+            OtpType::Totp
+        } else if otp_type.eq_ignore_ascii_case("hotp") {
+            OtpType::Hotp
+        } else {
+            return Err(UriError::UnsupportedType(otp_type.to_string()));
+        };
+        let label = percent_decode(label)?;
+        let (mut issuer, account) = match label.find(':') {
+            Some(index) => (
+                label[..index].to_string(),
+                label[index + 1..].trim_start().to_string(),
+            ),
+            None => (String::new(), label),
+        };
+        let mut parsed = OtpUri {
+            otp_type,
+            issuer: String::new(),
+            account,
+            secret: Vec::new(),
+            algorithm: OtpAlgorithmId::Sha1,
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+            counter: DEFAULT_COUNTER,
+            radix: DEFAULT_RADIX,
+            drift: 0,
+            salt: Vec::new(),
+        };
+        let (mut secret, mut counter) = (None, None);
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = match pair.find('=') {
+                Some(index) => (&pair[..index], percent_decode(&pair[index + 1..])?),
+                None => (pair, String::new()),
+            };
+            match name {
+                "secret" => secret = Some(decode_base32(&value)?),
+                "issuer" => issuer = value,
+                "algorithm" => {
+                    let algorithm = parse_algorithm(&value)
+                        .ok_or_else(|| UriError::UnsupportedAlgorithm(value.clone()))?;
+                    parsed.algorithm = OtpAlgorithmId::from_sha_type(algorithm);
+                }
+                "digits" => {
+                    parsed.digits = parse_number(name, &value)?;
+                    if !(1..=MAX_DIGITS).contains(&parsed.digits) {
+                        return Err(invalid_parameter(name, &value));
+                    }
+                }
+                "period" => {
+                    parsed.period = parse_number(name, &value)?;
+                    if parsed.period == 0 {
+                        return Err(invalid_parameter(name, &value));
+                    }
+                }
+                "counter" => counter = Some(parse_number(name, &value)?),
+                "radix" => {
+                    parsed.radix = parse_number(name, &value)?;
+                    if !(2..=36).contains(&parsed.radix) {
+                        return Err(invalid_parameter(name, &value));
+                    }
+                }
+                "drift" => parsed.drift = parse_number(name, &value)?,
+                "salt" => parsed.salt = decode_base32(&value)?,
+                _ => {}
+            }
+        }
+        parsed.secret = secret.ok_or(UriError::MissingSecret)?;
+        parsed.issuer = issuer;
+        if otp_type == OtpType::Hotp {
+            parsed.counter = counter.ok_or(UriError::MissingCounter)?;
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the algorithm of the URI.
+    pub const fn sha_type(&self) -> &'static ShaTypes {
+        self.algorithm.sha_type()
+    }
+
+    /// Returns the `Hotp` of the URI secret and salt, whatever the URI type.
+    pub fn to_hotp(&self) -> Hotp {
+        Hotp::new(self.secret.clone()).with_salt(&self.salt)
+    }
+}
+
+impl Totp<'_> {
+    /**
+    TOTP instance constructor from an `otpauth://totp/` provisioning URI.
+
+    # Example
+
+    ```
+    use ootp::totp::Totp;
+
+    let uri = "otpauth://totp/ACME%20Co:alice@example.com?secret=JBSWY3DPEHPK3PXP&algorithm=SHA256&digits=8&period=60";
+    let totp = Totp::from_uri(uri).unwrap();
+    assert_eq!((totp.digits, totp.period), (8, 60));
+    assert_eq!(totp.to_uri("ACME Co", "alice@example.com"), "otpauth://totp/ACME%20Co:alice%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60");
+    ```
+    */
+    pub fn from_uri(uri: &str) -> Result<Totp<'static>, UriError> {
+        let parsed = OtpUri::parse(uri)?;
+        if parsed.otp_type != OtpType::Totp {
+            return Err(UriError::UnsupportedType("hotp".to_string()));
+        }
+        Ok(Totp::secret(
+            parsed.secret,
+            CreateOption::Full {
+                digits: parsed.digits,
+                period: parsed.period,
+                algorithm: parsed.algorithm.sha_type(),
+            },
+        )
+        .with_radix(parsed.radix)
+        .with_fixed_drift(parsed.drift)
+        .with_salt(&parsed.salt))
+    }
+
+    /// Returns the `otpauth://totp/` provisioning URI of this `Totp` for the given `issuer`
+    /// and `account`. An empty `issuer` is omitted.
+    ///
+    /// A non-decimal radix, a fixed drift and a salt are written as the non-standard
+    /// `radix`, `drift` and `salt` parameters, see `OtpUri`. A period of `0` is written as
+    /// `1`, the period the codes are computed with.
+    pub fn to_uri(&self, issuer: &str, account: &str) -> String {
+        OtpUri {
+            otp_type: OtpType::Totp,
+            issuer: issuer.to_string(),
+            account: account.to_string(),
+            secret: self.hotp.secret(),
+            algorithm: OtpAlgorithmId::from_sha_type(self.algorithm),
+            digits: self.digits,
+            period: self.period.max(1),
+            counter: DEFAULT_COUNTER,
+            radix: self.radix,
+            drift: self.fixed_drift,
+            salt: self.hotp.salt().to_vec(),
+        }
+        .to_string()
+    }
+}
+
+impl Hotp {
+    /**
+    Parses an `otpauth://hotp/` provisioning URI, returning the `Hotp` and the `OtpUri`
+    holding its counter, digits and algorithm.
+
+    # Example
+
+    ```
+    use ootp::hotp::{Hotp, MakeOption};
+
+    let (hotp, uri) = Hotp::from_uri("otpauth://hotp/alice?secret=JBSWY3DPEHPK3PXP&counter=3").unwrap();
+    let otp = hotp.make(MakeOption::Full { counter: uri.counter, digits: uri.digits, algorithm: uri.sha_type() });
+    assert_eq!(otp, hotp.make(MakeOption::Counter(3)));
+    assert_eq!(hotp.to_uri("", "alice", uri.counter, uri.digits, uri.sha_type()), "otpauth://hotp/alice?secret=JBSWY3DPEHPK3PXP&algorithm=SHA1&digits=6&counter=3");
+    ```
+    */
+    pub fn from_uri(uri: &str) -> Result<(Hotp, OtpUri), UriError> {
+        let parsed = OtpUri::parse(uri)?;
+        if parsed.otp_type != OtpType::Hotp {
+            return Err(UriError::UnsupportedType("totp".to_string()));
+        }
+        Ok((parsed.to_hotp(), parsed))
+    }
+
+    /// Returns the `otpauth://hotp/` provisioning URI of this `Hotp` for the given `issuer`
+    /// and `account`, starting at `counter`. An empty `issuer` is omitted.
+    pub fn to_uri(
+        &self,
+        issuer: &str,
+        account: &str,
+        counter: u64,
+        digits: u32,
+        algorithm: &ShaTypes,
+    ) -> String {
+        OtpUri {
+            otp_type: OtpType::Hotp,
+            issuer: issuer.to_string(),
+            account: account.to_string(),
+            secret: self.secret(),
+            algorithm: OtpAlgorithmId::from_sha_type(algorithm),
+            digits,
+            period: DEFAULT_PERIOD,
+            counter,
+            radix: DEFAULT_RADIX,
+            drift: 0,
+            salt: self.salt().to_vec(),
+        }
+        .to_string()
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    match value.get(..prefix.len()) {
+        Some(head) if head.eq_ignore_ascii_case(prefix) => Some(&value[prefix.len()..]),
+        _ => None,
+    }
+}
+
+fn invalid_parameter(name: &str, value: &str) -> UriError {
+    UriError::InvalidParameter {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, UriError> {
+    value.parse().map_err(|_| invalid_parameter(name, value))
+}
+
+/// Percent-encodes every byte of `value` but the RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decodes the `%XX` sequences of `value`, which must yield UTF-8.
+fn percent_decode(value: &str) -> Result<String, UriError> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = value
+                .get(index + 1..index + 3)
+                .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                .ok_or(UriError::Encoding)?;
+            let byte = u8::from_str_radix(hex, 16).map_err(|_| UriError::Encoding)?;
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| UriError::Encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{percent_decode, percent_encode, OtpUri, UriError};
+    use crate::account::OtpType;
+    use crate::algorithm::{supported_algorithms, OtpAlgorithmId};
+    use crate::encoding::DecodeError;
+    use crate::hotp::{Hotp, MakeOption};
+    use crate::totp::tests::FixedClock;
+    use crate::totp::{CreateOption, Totp};
+
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn totp_uri_round_trip_test() {
+        let secret = "12345678901234567890".as_bytes().to_vec();
+        for &(label, algorithm) in supported_algorithms().iter().take(3) {
+            let totp = Totp::secret(
+                secret.clone(),
+                CreateOption::Full {
+                    digits: 8,
+                    period: 60,
+                    algorithm,
+                },
+            );
+            let uri = totp.to_uri("ACME Co", "alice@example.com");
+            assert!(uri.contains(&format!("algorithm={}", label)));
+            let parsed = Totp::from_uri(&uri).unwrap();
+            assert_eq!(parsed.to_uri("ACME Co", "alice@example.com"), uri);
+            assert_eq!(parsed.make_time(59), totp.make_time(59));
+        }
+    }
+
+    #[test]
+    fn radix_uri_round_trip_test() {
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Digits(8)).with_radix(16);
+        let uri = totp.to_uri("ACME", "alice");
+        assert!(uri.ends_with("&period=30&radix=16"));
+        let parsed = Totp::from_uri(&uri).unwrap();
+        assert_eq!(parsed.radix, 16);
+        assert_eq!(parsed.make_time(59), totp.make_time(59));
+        assert_eq!(parsed.to_uri("ACME", "alice"), uri);
+        for &radix in ["1", "37", "x"].iter() {
+            assert_eq!(
+                Totp::from_uri(&format!(
+                    "otpauth://totp/a?secret={}&radix={}",
+                    SECRET, radix
+                ))
+                .err(),
+                Some(UriError::InvalidParameter {
+                    name: "radix".to_string(),
+                    value: radix.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn drift_uri_round_trip_test() {
+        let clock = FixedClock(1_000);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default)
+            .with_clock(&clock)
+            .with_fixed_drift(-3);
+        let uri = totp.to_uri("ACME", "alice");
+        assert!(uri.ends_with("&period=30&drift=-3"));
+        let parsed = Totp::from_uri(&uri).unwrap().with_clock(&clock);
+        assert_eq!(parsed.fixed_drift, -3);
+        assert_eq!(parsed.make(), totp.make());
+        assert!(parsed.check(&totp.make(), Some(0)));
+        assert_eq!(parsed.to_uri("ACME", "alice"), uri);
+        // Default values are not emitted.
+        let plain = Totp::secret(vec![1, 2, 3], CreateOption::Default).to_uri("ACME", "alice");
+        assert!(!plain.contains("radix") && !plain.contains("drift"));
+    }
+
+    #[test]
+    fn salt_uri_round_trip_test() {
+        let clock = FixedClock(1_000);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Default)
+            .with_clock(&clock)
+            .with_salt(b"account-1");
+        let uri = totp.to_uri("ACME", "alice");
+        assert!(uri.ends_with("&period=30&salt=MFRWG33VNZ2C2MI"));
+        let parsed = Totp::from_uri(&uri).unwrap().with_clock(&clock);
+        assert_eq!(parsed.hotp.salt(), b"account-1");
+        assert_eq!(parsed.make(), totp.make());
+        assert_eq!(parsed.to_uri("ACME", "alice"), uri);
+        let hotp = Hotp::new(vec![1, 2, 3]).with_salt(b"account-1");
+        let uri = hotp.to_uri("ACME", "alice", 5, 6, &hmacsha::ShaTypes::Sha1);
+        let (parsed, params) = Hotp::from_uri(&uri).unwrap();
+        assert_eq!(params.salt, b"account-1");
+        assert_eq!(
+            parsed.make(MakeOption::Counter(5)),
+            hotp.make(MakeOption::Counter(5))
+        );
+    }
+
+    #[test]
+    fn zero_period_uri_test() {
+        let clock = FixedClock(1_000);
+        let totp = Totp::secret(vec![1, 2, 3], CreateOption::Period(0)).with_clock(&clock);
+        let uri = totp.to_uri("ACME", "alice");
+        assert!(uri.ends_with("&period=1"));
+        let parsed = Totp::from_uri(&uri).unwrap().with_clock(&clock);
+        assert_eq!(parsed.period, 1);
+        assert_eq!(parsed.make(), totp.make());
+    }
+
+    #[test]
+    fn non_standard_period_test() {
+        let uri = format!("otpauth://totp/ACME:alice?secret={}&period=45", SECRET);
+        let clock = FixedClock(1_000);
+        let totp = Totp::from_uri(&uri).unwrap().with_clock(&clock);
+        assert_eq!(totp.period, 45);
+        assert!(totp.to_uri("ACME", "alice").ends_with("&period=45"));
+        let otp = totp.make();
+        assert_eq!(otp, totp.make_time(990));
+        assert_ne!(otp, totp.make_time(989));
+        assert!(totp.check(&otp, Some(0)));
+        let token = Totp::secret(totp.hotp.secret(), CreateOption::Default).with_clock(&clock);
+        assert!(!totp.check(&token.make(), Some(0)));
+    }
+
+    #[test]
+    fn parse_label_test() {
+        let uri = format!(
+            "OTPAUTH://TOTP/ACME%20Co:%20alice%40example.com?secret={}&foo=bar",
+            SECRET.to_ascii_lowercase()
+        );
+        let parsed = OtpUri::parse(&uri).unwrap();
+        assert_eq!(parsed.issuer, "ACME Co");
+        assert_eq!(parsed.account, "alice@example.com");
+        assert_eq!(parsed.secret, b"12345678901234567890");
+        assert_eq!(parsed.algorithm, OtpAlgorithmId::Sha1);
+        assert_eq!((parsed.digits, parsed.period), (6, 30));
+        // The issuer parameter takes precedence over the label.
+        let uri = format!("otpauth://totp/Old:alice?secret={}&issuer=New", SECRET);
+        assert_eq!(OtpUri::parse(&uri).unwrap().issuer, "New");
+        let uri = format!("otpauth://totp/alice?secret={}", SECRET);
+        let parsed = OtpUri::parse(&uri).unwrap();
+        assert_eq!(
+            (parsed.issuer.as_str(), parsed.account.as_str()),
+            ("", "alice")
+        );
+        assert_eq!(
+            parsed.to_string(),
+            format!("{}&algorithm=SHA1&digits=6&period=30", uri)
+        );
+        assert!(!format!("{:?}", parsed).contains("49"));
+    }
+
+    #[test]
+    fn hotp_uri_test() {
+        let hotp = Hotp::new("12345678901234567890".as_bytes().to_vec());
+        let uri = hotp.to_uri("ACME", "alice", 5, 6, &hmacsha::ShaTypes::Sha1);
+        assert!(uri.starts_with("otpauth://hotp/ACME:alice?"));
+        assert!(uri.ends_with("&counter=5"));
+        let (parsed, params) = Hotp::from_uri(&uri).unwrap();
+        assert_eq!(params.otp_type, OtpType::Hotp);
+        assert_eq!(params.counter, 5);
+        assert_eq!(
+            parsed.make(MakeOption::Counter(params.counter)),
+            hotp.make(MakeOption::Counter(5))
+        );
+        assert_eq!(
+            Hotp::from_uri(&format!("otpauth://hotp/alice?secret={}", SECRET)).err(),
+            Some(UriError::MissingCounter)
+        );
+        assert_eq!(
+            Totp::from_uri(&uri).err(),
+            Some(UriError::UnsupportedType("hotp".to_string()))
+        );
+        let totp_uri = format!("otpauth://totp/alice?secret={}", SECRET);
+        assert_eq!(
+            Hotp::from_uri(&totp_uri).err(),
+            Some(UriError::UnsupportedType("totp".to_string()))
+        );
+    }
+
+    #[test]
+    fn uri_errors_test() {
+        let parse = |uri: &str| Totp::from_uri(uri).err();
+        assert_eq!(
+            parse("https://totp/alice?secret=GE"),
+            Some(UriError::Scheme)
+        );
+        assert_eq!(
+            parse("otpauth://motp/alice?secret=GEZA"),
+            Some(UriError::UnsupportedType("motp".to_string()))
+        );
+        assert_eq!(parse("otpauth://totp/alice"), Some(UriError::MissingSecret));
+        assert_eq!(
+            parse("otpauth://totp/alice?secret=GE1"),
+            Some(UriError::Secret(DecodeError::InvalidCharacter('1')))
+        );
+        assert_eq!(
+            parse(&format!(
+                "otpauth://totp/alice?secret={}&algorithm=MD5",
+                SECRET
+            )),
+            Some(UriError::UnsupportedAlgorithm("MD5".to_string()))
+        );
+        for &(name, value) in [
+            ("digits", "0"),
+            ("digits", "x"),
+            ("period", "0"),
+            ("period", "-1"),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                parse(&format!(
+                    "otpauth://totp/alice?secret={}&{}={}",
+                    SECRET, name, value
+                )),
+                Some(UriError::InvalidParameter {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+            );
+        }
+        assert_eq!(
+            parse("otpauth://totp/%E2%28?secret=GE"),
+            Some(UriError::Encoding)
+        );
+        assert_eq!(
+            parse("otpauth://totp/%4?secret=GE"),
+            Some(UriError::Encoding)
+        );
+        assert_eq!(
+            parse("otpauth://totp/%+1?secret=GE"),
+            Some(UriError::Encoding)
+        );
+    }
+
+    #[test]
+    fn percent_encoding_test() {
+        let value = "ACME Co: ünïcode/?&=%";
+        assert_eq!(percent_decode(&percent_encode(value)).unwrap(), value);
+        assert_eq!(percent_encode("a-b.c_d~e"), "a-b.c_d~e");
+        assert_eq!(percent_encode("a:b"), "a%3Ab");
+    }
+}
@@ -0,0 +1,638 @@
+use crate::totp::{Totp, VerifyResult};
+use std::collections::{BTreeMap, BTreeSet};
+
+/**
+A TOTP verifier that remembers the counter of the last accepted code and rejects any
+code at or below it, so that a code cannot be replayed within its validity window.
+
+# Example
+
+```
+use ootp::totp::{Totp, CreateOption};
+use ootp::validator::TotpValidator;
+
+let secret = "A strong shared secret".as_bytes().to_vec();
+let mut validator = TotpValidator::new(Totp::secret(secret, CreateOption::Default), 1);
+let otp = validator.totp.make();
+assert!(validator.verify(&otp));
+assert!(!validator.verify(&otp));
+```
+*/
+pub struct TotpValidator<'a> {
+    pub totp: Totp<'a>,
+    pub window: u64,
+    pub last_counter: Option<u64>,
+}
+
+impl<'a> TotpValidator<'a> {
+    /// Creates a validator accepting codes within `window` steps of the current time.
+    pub const fn new(totp: Totp<'a>, window: u64) -> Self {
+        Self {
+            totp,
+            window,
+            last_counter: None,
+        }
+    }
+
+    /// Returns the counter `otp` matches above the replay floor, without accepting it.
+    fn matched_counter(&self, otp: &str) -> Option<u64> {
+        self.totp
+            .matched_counter_above(otp, self.window, self.last_counter)
+    }
+
+    /// Verifies `otp` and, when it is valid, raises the replay floor to its counter.
+    pub fn verify(&mut self, otp: &str) -> bool {
+        match self.matched_counter(otp) {
+            Some(counter) => {
+                self.last_counter = Some(counter);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/**
+A verifier for hybrid schemes where every TOTP code is submitted along with a monotonic
+event counter: a code is accepted only when it is valid above the `TotpValidator` replay
+floor and its event counter is strictly greater than the last accepted one.
+
+A rejected submission updates neither floor.
+
+# Example
+
+```
+use ootp::totp::{Totp, CreateOption};
+use ootp::validator::HybridValidator;
+
+let secret = "A strong shared secret".as_bytes().to_vec();
+let mut validator = HybridValidator::new(Totp::secret(secret, CreateOption::Default), 1);
+let otp = validator.validator.totp.make();
+assert!(validator.verify(&otp, 2));
+assert!(!validator.verify(&validator.validator.totp.make_drift(1), 1));
+```
+*/
+pub struct HybridValidator<'a> {
+    pub validator: TotpValidator<'a>,
+    pub last_event: Option<u64>,
+}
+
+impl<'a> HybridValidator<'a> {
+    /// Creates a validator accepting codes within `window` steps of the current time.
+    pub const fn new(totp: Totp<'a>, window: u64) -> Self {
+        Self {
+            validator: TotpValidator::new(totp, window),
+            last_event: None,
+        }
+    }
+
+    /// Verifies `otp` along with `event_counter` and, when both are accepted,
+    /// raises both floors.
+    pub fn verify(&mut self, otp: &str, event_counter: u64) -> bool {
+        if self.last_event.is_some_and(|last| event_counter <= last) {
+            return false;
+        }
+        match self.validator.matched_counter(otp) {
+            Some(counter) => {
+                self.validator.last_counter = Some(counter);
+                self.last_event = Some(event_counter);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/**
+A TOTP verifier that remembers the counters accepted within the tolerance window and
+rejects only exact repeats, unlike the monotonic floor of `TotpValidator` which also
+rejects any older code, e.g. for slightly out-of-order submissions in distributed systems.
+
+# Example
+
+```
+use ootp::totp::{Totp, CreateOption};
+use ootp::validator::SlidingReplayWindow;
+
+let secret = "A strong shared secret".as_bytes().to_vec();
+let mut validator = SlidingReplayWindow::new(Totp::secret(secret, CreateOption::Default), 1);
+let current = validator.totp.make();
+assert!(validator.verify(&current));
+assert!(validator.verify(&validator.totp.make_drift(-1)));
+assert!(!validator.verify(&current));
+```
+*/
+pub struct SlidingReplayWindow<'a> {
+    pub totp: Totp<'a>,
+    pub window: u64,
+    pub accepted: BTreeSet<u64>,
+}
+
+impl<'a> SlidingReplayWindow<'a> {
+    /// Creates a validator accepting codes within `window` steps of the current time.
+    pub const fn new(totp: Totp<'a>, window: u64) -> Self {
+        Self {
+            totp,
+            window,
+            accepted: BTreeSet::new(),
+        }
+    }
+
+    /// Verifies `otp` and, when it is valid and its counter was not accepted before,
+    /// remembers its counter.
+    ///
+    /// Counters too old to ever match again are forgotten, so that the set stays bounded.
+    pub fn verify(&mut self, otp: &str) -> bool {
+        let accepted = &self.accepted;
+        let counter = match self
+            .totp
+            .matched_counter_where(otp, self.window, |counter| !accepted.contains(&counter))
+        {
+            Some(counter) => counter,
+            None => return false,
+        };
+        self.accepted.insert(counter);
+        // The current step is at least `counter - window`, so no code below
+        // `counter - 2 * window` can match anymore.
+        let oldest = counter.saturating_sub(self.window.saturating_mul(2));
+        self.accepted = self.accepted.split_off(&oldest);
+        true
+    }
+}
+
+/// The attempt counters of a `RateLimitedVerifier`, to share them between instances,
+/// e.g. through a database. With the `serde` feature, the state can be (de)serialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimitState {
+    /// The number of failed verifications since the limiting period started.
+    pub failures: u32,
+    /// The Unix time, in seconds, at which the failures are forgotten.
+    pub reset_at: u64,
+}
+
+/**
+A TOTP verifier that stops accepting any code after `max_failures` failed verifications
+within `period_secs` seconds of the first one, against brute-force attacks.
+
+A successful verification forgets the previous failures.
+
+# Example
+
+```
+use ootp::totp::{Totp, CreateOption};
+use ootp::validator::RateLimitedVerifier;
+
+let secret = "A strong shared secret".as_bytes().to_vec();
+let totp = Totp::secret(secret, CreateOption::Default);
+let mut verifier = RateLimitedVerifier::new(totp, 1, 3, 300);
+for _ in 0..3 {
+    assert!(!verifier.verify("wrong"));
+}
+assert!(verifier.is_limited());
+let state = verifier.to_state();
+assert_eq!(state.failures, 3);
+```
+*/
+pub struct RateLimitedVerifier<'a> {
+    pub totp: Totp<'a>,
+    pub window: u64,
+    pub max_failures: u32,
+    pub period_secs: u64,
+    pub state: RateLimitState,
+}
+
+impl<'a> RateLimitedVerifier<'a> {
+    /// Creates a verifier accepting codes within `window` steps of the current time,
+    /// with no failure recorded yet.
+    pub const fn new(totp: Totp<'a>, window: u64, max_failures: u32, period_secs: u64) -> Self {
+        Self::from_state(
+            totp,
+            window,
+            max_failures,
+            period_secs,
+            RateLimitState {
+                failures: 0,
+                reset_at: 0,
+            },
+        )
+    }
+
+    /// Creates a verifier resuming from a `state` saved by `to_state`.
+    pub const fn from_state(
+        totp: Totp<'a>,
+        window: u64,
+        max_failures: u32,
+        period_secs: u64,
+        state: RateLimitState,
+    ) -> Self {
+        Self {
+            totp,
+            window,
+            max_failures,
+            period_secs,
+            state,
+        }
+    }
+
+    /// Returns the current attempt counters, to be shared with other instances.
+    pub const fn to_state(&self) -> RateLimitState {
+        self.state
+    }
+
+    /// Returns a boolean indicating if verifications are currently refused.
+    pub fn is_limited(&self) -> bool {
+        self.is_limited_at(self.totp.clock.now_secs())
+    }
+
+    fn is_limited_at(&self, time: u64) -> bool {
+        time < self.state.reset_at && self.state.failures >= self.max_failures
+    }
+
+    /// Verifies `otp` unless the verifier is limited, recording a failure when it is invalid.
+    pub fn verify(&mut self, otp: &str) -> bool {
+        let time = self.totp.clock.now_secs();
+        if self.is_limited_at(time) {
+            return false;
+        }
+        if time >= self.state.reset_at {
+            self.state = RateLimitState {
+                failures: 0,
+                reset_at: time.saturating_add(self.period_secs),
+            };
+        }
+        if self
+            .totp
+            .matched_counter_above(otp, self.window, None)
+            .is_some()
+        {
+            self.state.failures = 0;
+            true
+        } else {
+            self.state.failures = self.state.failures.saturating_add(1);
+            false
+        }
+    }
+}
+
+/// The statistics accumulated by a `MonitoredVerifier`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationStats {
+    pub successes: u64,
+    pub failures: u64,
+    /// The number of successful verifications per drift, in steps, for the drifts seen.
+    pub drift_histogram: BTreeMap<i64, u64>,
+}
+
+impl VerificationStats {
+    /// Returns the number of verifications.
+    pub const fn total(&self) -> u64 {
+        self.successes.saturating_add(self.failures)
+    }
+
+    /// Returns the share of successful verifications, `0.0` before any verification.
+    pub fn success_ratio(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.successes as f64 / total as f64,
+        }
+    }
+
+    /// Returns the number of successful verifications with a drift of `drift` steps.
+    pub fn drift_count(&self, drift: i64) -> u64 {
+        self.drift_histogram.get(&drift).copied().unwrap_or(0)
+    }
+}
+
+/**
+A TOTP verifier accumulating verification counts and a drift histogram, e.g. to detect
+widespread clock skew or attacks. The histogram only holds the drifts seen, whatever the
+window.
+
+# Example
+
+```
+use ootp::totp::{Totp, CreateOption};
+use ootp::validator::MonitoredVerifier;
+
+let secret = "A strong shared secret".as_bytes().to_vec();
+let mut verifier = MonitoredVerifier::new(Totp::secret(secret, CreateOption::Default), 1);
+let otp = verifier.totp.make();
+assert!(verifier.verify(&otp));
+assert!(!verifier.verify("wrong"));
+assert_eq!(verifier.stats().success_ratio(), 0.5);
+```
+*/
+pub struct MonitoredVerifier<'a> {
+    pub totp: Totp<'a>,
+    pub window: u64,
+    stats: VerificationStats,
+}
+
+impl<'a> MonitoredVerifier<'a> {
+    /// Creates a verifier accepting codes within `window` steps of the current time.
+    pub fn new(totp: Totp<'a>, window: u64) -> Self {
+        Self {
+            totp,
+            window,
+            stats: VerificationStats::default(),
+        }
+    }
+
+    /// Returns the statistics accumulated so far.
+    pub const fn stats(&self) -> &VerificationStats {
+        &self.stats
+    }
+
+    /// Verifies `otp`, recording the outcome and the drift of a valid code.
+    pub fn verify(&mut self, otp: &str) -> bool {
+        let drift = match self.totp.verify_detailed(otp, self.window) {
+            VerifyResult::Valid { drift } => drift,
+            VerifyResult::FutureMatch { steps } => steps as i64,
+            VerifyResult::DigitCountMismatch { .. } | VerifyResult::Invalid => {
+                self.stats.failures = self.stats.failures.saturating_add(1);
+                return false;
+            }
+        };
+        self.stats.successes = self.stats.successes.saturating_add(1);
+        let count = self.stats.drift_histogram.entry(drift).or_insert(0);
+        *count = count.saturating_add(1);
+        true
+    }
+}
+
+/**
+A running mean and standard deviation of the drifts of successful verifications, to
+recommend a persistent per-user offset, e.g. for `Totp::with_fixed_drift`, when a
+token's clock is consistently skewed. With the `serde` feature, it can be (de)serialized.
+
+# Example
+
+```
+use ootp::validator::DriftStats;
+
+let mut stats = DriftStats::new();
+for _ in 0..5 {
+    stats.record(-1);
+}
+assert_eq!(stats.mean(), -1.0);
+assert_eq!(stats.suggest_offset(), Some(-1));
+```
+*/
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftStats {
+    /// The number of recorded drifts below which no offset is suggested, `5` by default.
+    pub min_samples: u64,
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Default for DriftStats {
+    fn default() -> Self {
+        Self {
+            min_samples: 5,
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+impl DriftStats {
+    /// Creates an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the drift, in steps, of a successful verification.
+    pub fn record(&mut self, drift: i64) {
+        // Welford's online algorithm, stable without storing the drifts.
+        self.count = self.count.saturating_add(1);
+        let delta = drift as f64 - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (drift as f64 - self.mean);
+    }
+
+    /// Returns the number of recorded drifts.
+    pub const fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the mean drift, `0.0` before any drift is recorded.
+    pub const fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the population standard deviation of the drifts, `0.0` before any drift
+    /// is recorded.
+    pub fn stddev(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => (self.m2 / count as f64).sqrt(),
+        }
+    }
+
+    /// Returns the rounded mean drift if at least `min_samples` drifts are recorded, they
+    /// are consistent, i.e. their standard deviation is below half a step, and it is
+    /// nonzero. Returns `None` otherwise.
+    pub fn suggest_offset(&self) -> Option<i64> {
+        if self.count < self.min_samples || self.stddev() >= 0.5 {
+            return None;
+        }
+        match self.mean.round() as i64 {
+            0 => None,
+            offset => Some(offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DriftStats, HybridValidator, MonitoredVerifier, RateLimitState, RateLimitedVerifier,
+        SlidingReplayWindow, TotpValidator,
+    };
+    use crate::totp::tests::FixedClock;
+    use crate::totp::{CreateOption, Totp};
+
+    fn totp(clock: &FixedClock) -> Totp<'_> {
+        Totp::secret(
+            "12345678901234567890".as_bytes().to_vec(),
+            CreateOption::Default,
+        )
+        .with_clock(clock)
+    }
+
+    #[test]
+    fn totp_validator_replay_test() {
+        let clock = FixedClock(90);
+        let mut validator = TotpValidator::new(totp(&clock), 1);
+        let previous = validator.totp.make_drift(-1);
+        let current = validator.totp.make();
+        assert!(validator.verify(&current));
+        assert_eq!(validator.last_counter, Some(3));
+        assert!(!validator.verify(&current));
+        assert!(!validator.verify(&previous));
+        assert!(validator.verify(&validator.totp.make_drift(1)));
+        assert_eq!(validator.last_counter, Some(4));
+    }
+
+    #[test]
+    fn hybrid_validator_test() {
+        let clock = FixedClock(90);
+        let mut validator = HybridValidator::new(totp(&clock), 1);
+        assert!(validator.verify(&validator.validator.totp.make_drift(-1), 5));
+        assert_eq!(validator.last_event, Some(5));
+        let current = validator.validator.totp.make();
+        assert!(!validator.verify(&current, 5));
+        assert!(!validator.verify(&current, 4));
+        assert_eq!(validator.validator.last_counter, Some(2));
+        assert!(validator.verify(&current, 6));
+        assert!(!validator.verify("000000", 7));
+        assert_eq!(validator.last_event, Some(6));
+    }
+
+    #[test]
+    fn sliding_replay_window_test() {
+        // Step 5 is the current one.
+        let clock = FixedClock(160);
+        let mut validator = SlidingReplayWindow::new(totp(&clock), 1);
+        let fifth = validator.totp.make_time(150);
+        let fourth = validator.totp.make_time(120);
+        assert!(validator.verify(&fifth));
+        assert!(validator.verify(&fourth));
+        assert!(!validator.verify(&fifth));
+        assert!(!validator.verify(&fourth));
+        assert_eq!(
+            validator.accepted.iter().copied().collect::<Vec<_>>(),
+            [4, 5]
+        );
+        assert!(validator.verify(&validator.totp.make_time(180)));
+        assert!(!validator.verify("000000"));
+        // The monotonic floor rejects the same out-of-order submissions.
+        let mut floor = TotpValidator::new(totp(&clock), 1);
+        assert!(floor.verify(&fifth));
+        assert!(!floor.verify(&fourth));
+    }
+
+    #[test]
+    fn sliding_replay_window_prune_test() {
+        let clock = FixedClock(160);
+        let mut validator = SlidingReplayWindow::new(totp(&clock), 1);
+        validator.accepted.extend([0, 1, 2, 3]);
+        assert!(validator.verify(&validator.totp.make_time(150)));
+        assert_eq!(
+            validator.accepted.iter().copied().collect::<Vec<_>>(),
+            [3, 5]
+        );
+    }
+
+    #[test]
+    fn rate_limit_state_test() {
+        let clock = FixedClock(1_000);
+        let mut verifier = RateLimitedVerifier::new(totp(&clock), 1, 3, 300);
+        assert!(!verifier.verify("000000"));
+        assert!(!verifier.verify("111111"));
+        let state = verifier.to_state();
+        assert_eq!(
+            state,
+            RateLimitState {
+                failures: 2,
+                reset_at: 1_300
+            }
+        );
+        let mut restored = RateLimitedVerifier::from_state(totp(&clock), 1, 3, 300, state);
+        assert!(!restored.is_limited());
+        assert!(!restored.verify("222222"));
+        assert!(restored.is_limited());
+        let otp = restored.totp.make();
+        assert!(!restored.verify(&otp));
+        assert_eq!(restored.to_state().failures, 3);
+        let later = FixedClock(1_300);
+        let mut reset = RateLimitedVerifier::from_state(totp(&later), 1, 3, 300, restored.state);
+        assert!(!reset.is_limited());
+        assert!(reset.verify(&reset.totp.make()));
+        assert_eq!(reset.to_state().failures, 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rate_limit_state_serde_test() {
+        let clock = FixedClock(1_000);
+        let mut verifier = RateLimitedVerifier::new(totp(&clock), 1, 3, 300);
+        assert!(!verifier.verify("000000"));
+        assert!(!verifier.verify("111111"));
+        let json = serde_json::to_string(&verifier.to_state()).unwrap();
+        let state: RateLimitState = serde_json::from_str(&json).unwrap();
+        let mut restored = RateLimitedVerifier::from_state(totp(&clock), 1, 3, 300, state);
+        assert!(!restored.verify("222222"));
+        assert!(restored.is_limited());
+    }
+
+    #[test]
+    fn monitored_verifier_test() {
+        let clock = FixedClock(1_000);
+        let mut verifier = MonitoredVerifier::new(totp(&clock), 2);
+        assert_eq!(verifier.stats().success_ratio(), 0.0);
+        assert!(verifier.verify(&verifier.totp.make()));
+        assert!(verifier.verify(&verifier.totp.make()));
+        assert!(verifier.verify(&verifier.totp.make_drift(1)));
+        assert!(!verifier.verify("000000"));
+        assert!(!verifier.verify(&verifier.totp.make_drift(3)));
+        let stats = verifier.stats();
+        assert_eq!(stats.successes, 3);
+        assert_eq!(stats.failures, 2);
+        assert_eq!(stats.total(), 5);
+        assert_eq!(stats.success_ratio(), 0.6);
+        assert_eq!(
+            stats.drift_histogram.iter().collect::<Vec<_>>(),
+            [(&0, &2), (&1, &1)]
+        );
+        assert_eq!(stats.drift_count(0), 2);
+        assert_eq!(stats.drift_count(1), 1);
+        assert_eq!(stats.drift_count(-2), 0);
+        assert_eq!(stats.drift_count(3), 0);
+        assert_eq!(stats.drift_count(i64::MIN), 0);
+    }
+
+    #[test]
+    fn monitored_verifier_huge_window_test() {
+        let clock = FixedClock(1_000);
+        let mut verifier = MonitoredVerifier::new(totp(&clock), u64::MAX);
+        assert!(verifier.verify(&verifier.totp.make()));
+        assert!(verifier.verify(&verifier.totp.make_drift(-2)));
+        assert_eq!(verifier.stats().drift_count(0), 1);
+        assert_eq!(verifier.stats().drift_count(-2), 1);
+        assert_eq!(verifier.stats().drift_histogram.len(), 2);
+    }
+
+    #[test]
+    fn drift_stats_test() {
+        let mut stats = DriftStats::new();
+        assert_eq!((stats.mean(), stats.stddev()), (0.0, 0.0));
+        assert_eq!(stats.suggest_offset(), None);
+        for _ in 0..4 {
+            stats.record(2);
+        }
+        assert_eq!(stats.suggest_offset(), None);
+        stats.record(2);
+        assert_eq!(stats.count(), 5);
+        assert_eq!((stats.mean(), stats.stddev()), (2.0, 0.0));
+        assert_eq!(stats.suggest_offset(), Some(2));
+        // Inconsistent drifts suggest no offset.
+        let mut noisy = DriftStats::new();
+        for &drift in [-1, 1, -1, 1, 0, 2].iter() {
+            noisy.record(drift);
+        }
+        assert!(noisy.stddev() > 0.5);
+        assert_eq!(noisy.suggest_offset(), None);
+        // A consistent zero drift needs no offset.
+        let mut zero = DriftStats::new();
+        for _ in 0..5 {
+            zero.record(0);
+        }
+        assert_eq!(zero.suggest_offset(), None);
+    }
+}
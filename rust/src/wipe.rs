@@ -0,0 +1,8 @@
+use std::hint::black_box;
+
+/// Overwrites `buffer` with zeros, `black_box` keeping the compiler from eliding the
+/// writes to a buffer about to be freed.
+pub(crate) fn wipe(buffer: &mut [u8]) {
+    buffer.fill(0);
+    black_box(buffer);
+}
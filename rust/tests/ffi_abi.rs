@@ -0,0 +1,147 @@
+//! Calls the `ffi` functions through their C declarations, as a C program linked
+//! against the library would, and checks that include/ootp.h matches them.
+#![cfg(feature = "ffi")]
+
+use ootp::algorithm::OtpAlgorithmId;
+use ootp::ffi::{
+    OOTP_ERR_BUFFER_TOO_SMALL, OOTP_ERR_INVALID_ARGUMENT, OOTP_ERR_NULL_POINTER, OOTP_ERR_PANIC,
+    OOTP_MAX_WINDOW_STEPS, OOTP_OK,
+};
+use std::ptr;
+
+// Mirrors the prototypes of include/ootp.h.
+extern "C" {
+    fn ootp_totp_make(
+        secret_ptr: *const u8,
+        secret_len: usize,
+        digits: u32,
+        period: u64,
+        algo_id: u8,
+        out_buf: *mut u8,
+        out_len: usize,
+    ) -> i32;
+    fn ootp_totp_check(
+        secret_ptr: *const u8,
+        secret_len: usize,
+        digits: u32,
+        period: u64,
+        algo_id: u8,
+        otp_ptr: *const u8,
+        otp_len: usize,
+        breadth_secs: u64,
+    ) -> i32;
+}
+
+const HEADER: &str = include_str!("../include/ootp.h");
+const SECRET: &[u8] = b"12345678901234567890";
+
+#[test]
+fn c_abi_round_trip_test() {
+    let mut out = [0xffu8; 7];
+    let status =
+        unsafe { ootp_totp_make(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, out.as_mut_ptr(), 7) };
+    assert_eq!(status, OOTP_OK);
+    assert_eq!(out[6], 0);
+    assert!(out[..6].iter().all(u8::is_ascii_digit));
+    // 30 seconds, one step, in case a step boundary was crossed since the code was made.
+    let status =
+        unsafe { ootp_totp_check(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, out.as_ptr(), 6, 30) };
+    assert_eq!(status, 1);
+}
+
+#[test]
+fn c_abi_window_limit_test() {
+    let otp = b"123456";
+    let check = |breadth_secs| unsafe {
+        ootp_totp_check(
+            SECRET.as_ptr(),
+            SECRET.len(),
+            6,
+            30,
+            0,
+            otp.as_ptr(),
+            6,
+            breadth_secs,
+        )
+    };
+    assert!(check(OOTP_MAX_WINDOW_STEPS * 30 + 29) >= 0);
+    assert_eq!(
+        check((OOTP_MAX_WINDOW_STEPS + 1) * 30),
+        OOTP_ERR_INVALID_ARGUMENT
+    );
+    assert_eq!(check(u64::MAX), OOTP_ERR_INVALID_ARGUMENT);
+}
+
+#[test]
+fn c_abi_invalid_pointers_test() {
+    let mut out = [0u8; 7];
+    let otp = b"123456";
+    unsafe {
+        assert_eq!(
+            ootp_totp_make(ptr::null(), 0, 6, 30, 0, out.as_mut_ptr(), 7),
+            OOTP_ERR_NULL_POINTER
+        );
+        assert_eq!(
+            ootp_totp_make(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, ptr::null_mut(), 7),
+            OOTP_ERR_NULL_POINTER
+        );
+        assert_eq!(
+            ootp_totp_make(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, out.as_mut_ptr(), 6),
+            OOTP_ERR_BUFFER_TOO_SMALL
+        );
+        assert_eq!(
+            ootp_totp_check(ptr::null(), 0, 6, 30, 0, otp.as_ptr(), 6, 30),
+            OOTP_ERR_NULL_POINTER
+        );
+        assert_eq!(
+            ootp_totp_check(SECRET.as_ptr(), SECRET.len(), 6, 30, 0, ptr::null(), 6, 30),
+            OOTP_ERR_NULL_POINTER
+        );
+    }
+    assert_eq!(out, [0u8; 7]);
+}
+
+#[test]
+fn c_abi_invalid_utf8_test() {
+    let otp = b"12\xff\xfe56";
+    let status = unsafe {
+        ootp_totp_check(
+            SECRET.as_ptr(),
+            SECRET.len(),
+            6,
+            30,
+            0,
+            otp.as_ptr(),
+            otp.len(),
+            30,
+        )
+    };
+    assert_eq!(status, OOTP_ERR_INVALID_ARGUMENT);
+}
+
+#[test]
+fn header_matches_test() {
+    for (name, value) in [
+        ("OOTP_OK", OOTP_OK),
+        ("OOTP_ERR_NULL_POINTER", OOTP_ERR_NULL_POINTER),
+        ("OOTP_ERR_BUFFER_TOO_SMALL", OOTP_ERR_BUFFER_TOO_SMALL),
+        ("OOTP_ERR_INVALID_ARGUMENT", OOTP_ERR_INVALID_ARGUMENT),
+        ("OOTP_ERR_PANIC", OOTP_ERR_PANIC),
+        ("OOTP_ALGORITHM_SHA1", OtpAlgorithmId::Sha1 as i32),
+        ("OOTP_ALGORITHM_SHA256", OtpAlgorithmId::Sha256 as i32),
+        ("OOTP_ALGORITHM_SHA512", OtpAlgorithmId::Sha512 as i32),
+        ("OOTP_ALGORITHM_SHA3_256", OtpAlgorithmId::Sha3_256 as i32),
+        ("OOTP_ALGORITHM_SHA3_512", OtpAlgorithmId::Sha3_512 as i32),
+    ] {
+        let define = format!("#define {} {}\n", name, value);
+        assert!(HEADER.contains(&define), "missing `{}`", define.trim_end());
+    }
+    let define = format!("#define OOTP_MAX_WINDOW_STEPS {}\n", OOTP_MAX_WINDOW_STEPS);
+    assert!(HEADER.contains(&define));
+    assert!(HEADER.contains(
+        "int32_t ootp_totp_make(const uint8_t *secret_ptr, size_t secret_len, uint32_t digits,"
+    ));
+    assert!(HEADER.contains(
+        "int32_t ootp_totp_check(const uint8_t *secret_ptr, size_t secret_len, uint32_t digits,"
+    ));
+}